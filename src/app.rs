@@ -1,27 +1,70 @@
 use tui::{
     backend::Backend,
-    layout::{Constraint, Direction, Layout, Rect},
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
     style::{Color, Style},
-    widgets::{Block, Borders, Row, Table},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Row, Table},
     Frame,
 };
 
 use std::{
+    collections::HashMap,
     env,
     ffi::OsStr,
     fs, io,
     path::{Path, PathBuf},
+    process::{Command, ExitStatus, Output, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
     thread,
-    thread::JoinHandle,
 };
 
+use ::trash::TrashItem;
+use crossterm::event::KeyEvent;
+
+mod config;
+pub use config::Action;
+use config::{expand_tilde, Config, KeyMap, Theme};
+mod form;
+use form::{Form, FormKind};
+mod jobs;
+mod line_edit;
+use jobs::{
+    conflict::{Conflict, ConflictAction},
+    copy::{self, CopyOptions},
+    delete, JobContext, JobKind, JobManager,
+};
 mod popup;
-use popup::Popup;
+pub use popup::Button;
+use popup::{Popup, PopupAction};
 mod panel;
-use panel::Panel;
+use panel::{
+    dir_size::{DirSizes, SizeState},
+    find::{FileKind, FindCriteria},
+    grep::GrepCriteria,
+    search::{Search, SearchMode},
+    trash, Panel,
+};
+mod preview;
+use preview::Preview;
+mod prompt;
+use prompt::{Prompt, PromptKind};
+mod rename;
+use rename::{CaseConversion, RenameRule};
+mod shell;
+use shell::Placeholders;
+mod viewer;
+use viewer::Viewer;
+
+const MAX_HISTORY_LEN: usize = 100;
 
-const ACTIVE_COLOR: Color = Color::LightGreen;
-const INACTIVE_COLOR: Color = Color::DarkGray;
+#[cfg(unix)]
+const DEFAULT_EDITOR: &str = "vi";
+#[cfg(not(unix))]
+const DEFAULT_EDITOR: &str = "notepad";
+#[cfg(unix)]
+const DEFAULT_PAGER: &str = "less";
+#[cfg(not(unix))]
+const DEFAULT_PAGER: &str = "more";
 
 #[derive(PartialEq)]
 pub enum ActivePanel {
@@ -33,66 +76,234 @@ pub struct App {
     cur_panel: ActivePanel,
     left_panel: Panel,
     right_panel: Panel,
-    search_str: String,
+    search: Search,
     popup: Option<Popup>,
-    operations: Vec<JoinHandle<io::Result<()>>>,
+    prompt: Option<Prompt>,
+    form: Option<Form>,
+    viewer: Option<Viewer>,
+    // Program to run in the terminal, started by main which owns the terminal
+    // The flag tells whether to wait for Enter afterwards so the output can be read
+    external_command: Option<(Command, bool)>,
+    // Command line running in the background, its output is shown once done
+    running_command: Option<(String, Receiver<io::Result<Output>>)>,
+    jobs: JobManager,
+    // Errors of finished jobs waiting for the open popup to be closed
+    job_errors: Vec<String>,
+    show_jobs: bool,
+    // The other panel shows a preview of the entry under the cursor
+    quick_view: bool,
+    preview: Preview,
+    dir_sizes: DirSizes,
+    // Earlier inputs per kind of prompt, the oldest first
+    prompt_history: HashMap<PromptKind, Vec<String>>,
+    // Earlier inputs of the copy and move destination
+    destination_history: Vec<String>,
+    copy_options: CopyOptions,
+    config: Config,
+    should_quit: bool,
 }
 
 impl App {
     pub fn new() -> Self {
-        let start_path: PathBuf;
-
-        // Determine the home path
-        if cfg![windows] {
-            start_path = PathBuf::from(format![
-                "{}{}",
-                env::var("HOMEDRIVE").unwrap(),
-                env::var("HOMEPATH").unwrap()
-            ]);
-        } else {
-            start_path = PathBuf::from(env::var("HOME").unwrap());
-        }
+        let mut popup: Option<Popup> = None;
+
+        // Fall back to the defaults if the config file is invalid
+        let config: Config = match Config::load() {
+            Ok(x) => x,
+            Err(error) => {
+                popup = Some(Popup::new(
+                    "Error",
+                    error,
+                    Some(Style::default().fg(Color::Red)),
+                ));
+                Config::default()
+            }
+        };
+
+        let dir_sizes: DirSizes = DirSizes::default();
 
         return App {
             cur_panel: ActivePanel::Left,
-            left_panel: Panel::new(&start_path),
-            right_panel: Panel::new(&start_path),
-            search_str: String::new(),
-            popup: None,
-            operations: Vec::new(),
+            left_panel: Panel::new(&config.left_start, &config.panel, dir_sizes.clone()),
+            right_panel: Panel::new(&config.right_start, &config.panel, dir_sizes.clone()),
+            search: Search::new(SearchMode::Substring),
+            popup,
+            prompt: None,
+            form: None,
+            viewer: None,
+            external_command: None,
+            running_command: None,
+            jobs: JobManager::new(),
+            job_errors: Vec::new(),
+            show_jobs: false,
+            quick_view: false,
+            preview: Preview::new(),
+            dir_sizes,
+            prompt_history: HashMap::new(),
+            destination_history: Vec::new(),
+            copy_options: CopyOptions {
+                dereference: config.behavior.dereference_symlinks,
+            },
+            config,
+            should_quit: false,
         };
     }
 
+    // Plain chars are bound only as long as no search is in progress
+    pub fn get_action(&self, key: KeyEvent) -> Option<Action> {
+        return self.config.keymap.get_action(key, !self.search.is_empty());
+    }
+
     pub fn is_popup(&self) -> bool {
         return self.popup.is_some();
     }
 
+    pub fn is_prompt(&self) -> bool {
+        return self.prompt.is_some();
+    }
+
+    pub fn is_renaming(&self) -> bool {
+        return self.left_panel.is_renaming() || self.right_panel.is_renaming();
+    }
+
+    pub fn is_viewer(&self) -> bool {
+        return self.viewer.is_some();
+    }
+
+    pub fn is_jobs_view(&self) -> bool {
+        return self.show_jobs;
+    }
+
+    pub fn is_form(&self) -> bool {
+        return self.form.is_some();
+    }
+
+    pub fn has_jobs(&self) -> bool {
+        return !self.jobs.is_empty();
+    }
+
+    pub fn is_running_command(&self) -> bool {
+        return self.running_command.is_some();
+    }
+
+    pub fn is_computing_dir_sizes(&self) -> bool {
+        return self.dir_sizes.is_computing();
+    }
+
+    pub fn is_searching(&self) -> bool {
+        return self.left_panel.is_searching() || self.right_panel.is_searching();
+    }
+
+    pub fn should_quit(&self) -> bool {
+        return self.should_quit;
+    }
+
     pub fn open_dir(&mut self) {
         self.get_cur_panel().open_dir();
-        self.search_str.clear();
+        self.search.clear();
     }
 
     pub fn open(&mut self) {
         let cur_obj: PathBuf = self.get_cur_panel().get_cur_obj();
-        self.search_str.clear();
+        self.search.clear();
+
+        if self.get_cur_panel().is_trash() {
+            self.restore_trash_items();
+            return;
+        }
+
+        // Files found by a search are opened, folders are jumped to
+        if self.get_cur_panel().is_results() && cur_obj.is_dir() {
+            self.get_cur_panel().jump_to_result();
+            return;
+        }
 
         if cur_obj.is_dir() {
             self.get_cur_panel().open_dir();
         } else {
-            if let Err(error) = open::that(cur_obj.to_str().unwrap()) {
-                self.popup = Some(Popup::new(
-                    "Error",
-                    format!["Failed to open {} [Error: {}]", cur_obj.display(), error],
-                    None,
-                ));
-                return;
+            // Without a desktop to hand the file to, e.g. over SSH, it is viewed here
+            if open::that(&cur_obj).is_err() {
+                self.open_viewer();
             };
         }
     }
 
+    pub fn open_viewer(&mut self) {
+        let cur_obj: PathBuf = self.get_cur_panel().get_cur_obj();
+
+        if cur_obj.as_os_str().is_empty() || self.get_cur_panel().is_trash() {
+            return;
+        }
+
+        match Viewer::open(&cur_obj) {
+            Ok(x) => self.viewer = Some(x),
+            Err(error) => self.popup = Some(Popup::new("Error", error, None)),
+        }
+    }
+
+    pub fn edit_file(&mut self) {
+        self.run_on_cur_obj(&["VISUAL", "EDITOR"], DEFAULT_EDITOR);
+    }
+
+    pub fn page_file(&mut self) {
+        self.run_on_cur_obj(&["PAGER"], DEFAULT_PAGER);
+    }
+
+    // The program is taken from the first set environment variable
+    fn run_on_cur_obj(&mut self, vars: &[&str], default: &str) {
+        let cur_obj: PathBuf = self.get_cur_panel().get_cur_obj();
+
+        if cur_obj.as_os_str().is_empty() || self.get_cur_panel().is_trash() {
+            return;
+        }
+
+        if cur_obj.is_dir() {
+            self.popup = Some(Popup::new(
+                "Error",
+                format!["{} is a folder", cur_obj.display()],
+                None,
+            ));
+            return;
+        }
+
+        let mut command: Command = get_env_command(vars, default);
+        command.arg(&cur_obj);
+
+        if let Some(dir) = cur_obj.parent() {
+            command.current_dir(dir);
+        }
+
+        self.external_command = Some((command, false));
+    }
+
+    pub fn take_external_command(&mut self) -> Option<(Command, bool)> {
+        return self.external_command.take();
+    }
+
+    // Called by main once the terminal is restored
+    pub fn finish_external_command(&mut self, result: io::Result<ExitStatus>) {
+        self.refresh();
+
+        let error: String = match result {
+            Ok(x) if x.success() => return,
+            Ok(x) => format!["The program exited with {}", x],
+            Err(x) => format!["Failed to start the program [Error: {}]", x],
+        };
+
+        self.popup = Some(Popup::new("Error", error, None));
+    }
+
+    pub fn handle_viewer_key(&mut self, key: KeyEvent) {
+        if let Some(viewer) = self.viewer.as_mut() {
+            if !viewer.handle_key(key) {
+                self.viewer = None;
+            }
+        }
+    }
+
     pub fn leave_dir(&mut self) {
         self.get_cur_panel().leave_dir();
-        self.search_str.clear();
+        self.search.clear();
     }
 
     pub fn next(&mut self) {
@@ -117,245 +328,1573 @@ impl App {
         } else {
             self.cur_panel = ActivePanel::Left;
         }
-        self.search_str.clear();
+        self.search.clear();
     }
 
     pub fn jump_to_first_matching(&mut self, ch: char) {
-        self.search_str.push(ch);
-
-        let search_str_clone: String = self.search_str.clone();
-        self.get_cur_panel()
-            .jump_to_first_matching(&search_str_clone);
+        self.search.push_char(ch);
+        self.jump_to_best_match();
     }
 
     pub fn clear_search_str(&mut self) {
-        self.search_str.clear();
+        self.search.clear();
     }
 
     pub fn pop_char_from_search_str(&mut self) {
-        self.search_str.pop();
+        self.search.pop_char();
+        self.jump_to_best_match();
     }
 
-    pub fn open_help_popup(&mut self) {
-        self.popup = Some(Popup::new(
-            "Help",
-            concat![
-                "F1 - Show this help\n",
-                "F2 - Copy\n",
-                "F3 - Move\n",
-                "F5 - Refresh\n",
-                "F12 - Terminate sfmanager\n", // TODO -> use env
-                "Arrow up - Go one entry up\n",
-                "Arrow down - Go one entry down\n",
-                "Home - Go to the first entry\n",
-                "End - Go to the last entry\n",
-                "Arrow right - Enter folder\n",
-                "Enter - Enter folder\n",
-                "Arrow left - Leave folder\n",
-                "Backspace - Delete last char from search string\n",
-                "Tab - Switch current panel\n",
-                "Delete - Delete\n",
-                "Esc - Clear search string\n",
-            ],
-            None,
-        ));
+    pub fn next_search_mode(&mut self) {
+        self.search.next_mode();
+        self.jump_to_best_match();
+    }
+
+    pub fn next_match(&mut self) {
+        let panel: &mut Panel = match self.cur_panel {
+            ActivePanel::Left => &mut self.left_panel,
+            ActivePanel::Right => &mut self.right_panel,
+        };
+
+        panel.jump_to_next_match(&self.search, true);
     }
 
-    pub fn close_popup(&mut self) {
-        self.popup = None;
+    pub fn previous_match(&mut self) {
+        let panel: &mut Panel = match self.cur_panel {
+            ActivePanel::Left => &mut self.left_panel,
+            ActivePanel::Right => &mut self.right_panel,
+        };
+
+        panel.jump_to_next_match(&self.search, false);
     }
 
-    pub fn copy_objects(&mut self) {
-        let src_dest_paths: (PathBuf, PathBuf) = self.get_copy_move_path();
-        let src_path = src_dest_paths.0;
-        let dest_path = src_dest_paths.1;
-
-        self.operations
-            .push(thread::spawn(move || -> io::Result<()> {
-                if src_path.is_dir() {
-                    copy_recursively(&src_path, &dest_path)?;
-                } else {
-                    fs::copy(&src_path, &dest_path)?;
-                }
+    fn jump_to_best_match(&mut self) {
+        let panel: &mut Panel = match self.cur_panel {
+            ActivePanel::Left => &mut self.left_panel,
+            ActivePanel::Right => &mut self.right_panel,
+        };
 
-                return Ok(());
-            }));
+        panel.jump_to_best_match(&self.search);
     }
 
-    pub fn move_objects(&mut self) {
-        let src_dest_paths: (PathBuf, PathBuf) = self.get_copy_move_path();
-        let src_path = src_dest_paths.0;
-        let dest_path = src_dest_paths.1;
-
-        self.operations
-            .push(thread::spawn(move || -> io::Result<()> {
-                if src_path.is_dir() {
-                    copy_recursively(&src_path, &dest_path)?;
-                    fs::remove_dir_all(&src_path)?;
-                } else {
-                    fs::copy(&src_path, &dest_path)?;
-                    fs::remove_file(&src_path)?;
-                }
+    pub fn toggle_mark(&mut self) {
+        self.get_cur_panel().toggle_mark();
+    }
 
-                return Ok(());
-            }));
+    pub fn mark_all(&mut self) {
+        self.get_cur_panel().mark_all();
     }
 
-    pub fn refresh(&mut self) {
-        self.left_panel.update_items();
-        self.right_panel.update_items();
+    pub fn unmark_all(&mut self) {
+        self.get_cur_panel().unmark_all();
     }
 
-    pub fn delete_objects(&mut self) {
-        let cur_obj: PathBuf = self.get_cur_panel().get_cur_obj();
+    pub fn invert_marks(&mut self) {
+        self.get_cur_panel().invert_marks();
+    }
+
+    pub fn open_mark_glob_prompt(&mut self) {
+        self.show_prompt(Prompt::new(PromptKind::MarkGlob, "Mark by glob pattern"));
+    }
+
+    pub fn open_filter_prompt(&mut self) {
+        let filter: String = self.get_cur_panel().get_filter_text();
+
+        self.show_prompt(
+            Prompt::new(
+                PromptKind::Filter,
+                "Filter files (glob pattern or re:regex)",
+            )
+            .with_input(filter),
+        );
+    }
+
+    pub fn open_create_dir_prompt(&mut self) {
+        if self.get_cur_panel().is_trash() || self.get_cur_panel().is_results() {
+            return;
+        }
+
+        let dir: PathBuf = self.get_cur_panel().get_path();
+
+        self.show_prompt(
+            Prompt::new(
+                PromptKind::CreateDir,
+                "Create folder (missing parent folders are created too, Tab completes)",
+            )
+            .with_completion(&dir),
+        );
+    }
+
+    pub fn open_create_file_prompt(&mut self) {
+        if self.get_cur_panel().is_trash() || self.get_cur_panel().is_results() {
+            return;
+        }
+
+        let dir: PathBuf = self.get_cur_panel().get_path();
+
+        self.show_prompt(
+            Prompt::new(PromptKind::CreateFile, "Create empty file (Tab completes)")
+                .with_completion(&dir),
+        );
+    }
+
+    // Relative paths start at the panel path, the cursor lands on the new entry
+    fn create_object(&mut self, input: &str, is_dir: bool) {
+        if input.trim().is_empty() {
+            return;
+        }
+
+        let dir: PathBuf = self.get_cur_panel().get_path();
+        let path: PathBuf = dir.join(expand_tilde(input.trim()));
 
-        if let Err(error) = trash::delete(&cur_obj) {
+        let result: io::Result<()> = match (path.symlink_metadata().is_ok(), is_dir) {
+            (true, _) => Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                "The entry already exists",
+            )),
+            (false, true) => fs::create_dir_all(&path),
+            (false, false) => fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+                .map(|_| ()),
+        };
+
+        if let Err(error) = result {
             self.popup = Some(Popup::new(
                 "Error",
-                format!["Failed to delete {} [Error: {}]", cur_obj.display(), error],
+                format!["Failed to create {} [Error: {}]", path.display(), error],
                 None,
             ));
+            return;
         }
 
-        // self.operations
-        //     .push(thread::spawn(move || -> io::Result<()> {
-        //         if cur_obj.is_dir() {
-        //             fs::remove_dir_all(&cur_obj)?;
-        //         } else {
-        //             fs::remove_file(&cur_obj)?;
-        //         }
+        self.get_cur_panel().update_items();
 
-        //         return Ok(());
-        //     }));
+        // Nested paths select their first folder
+        if let Ok(relative) = path.strip_prefix(&dir) {
+            if let Some(first) = relative.components().next() {
+                self.get_cur_panel().select_obj(&dir.join(first));
+            }
+        }
     }
 
-    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
-        if self.popup.is_some() {
-            self.popup.as_mut().unwrap().render(f);
+    pub fn open_command_prompt(&mut self) {
+        if self.get_cur_panel().is_trash() || self.get_cur_panel().is_results() {
             return;
         }
 
-        self.refresh();
-
-        let ui_chunks: Vec<Rect> = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref())
-            .split(f.size());
+        let dir: PathBuf = self.get_cur_panel().get_path();
 
-        let panel_chunks: Vec<Rect> = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
-            .split(ui_chunks[0]);
+        self.show_prompt(
+            Prompt::new(
+                PromptKind::Command,
+                "Command [%f file, %s selected, %d folder, %D other folder, ! prefix to run interactively, Tab completes]",
+            )
+            .with_completion(&dir),
+        );
+    }
 
-        let mut color: Color = match self.cur_panel {
-            ActivePanel::Left => ACTIVE_COLOR,
-            ActivePanel::Right => INACTIVE_COLOR,
+    // Commands starting with ! get the terminal, the output of the others is
+    // collected in the background and shown in a popup
+    fn run_command_line(&mut self, text: &str) {
+        let (text, is_interactive) = match text.strip_prefix('!') {
+            Some(x) => (x.trim(), true),
+            None => (text.trim(), false),
         };
 
-        self.left_panel.render(panel_chunks[0], f, color);
+        if text.is_empty() {
+            return;
+        }
+
+        if self.running_command.is_some() {
+            self.popup = Some(Popup::new(
+                "Error",
+                "Wait for the running command to finish first",
+                None,
+            ));
+            return;
+        }
 
-        color = match self.cur_panel {
-            ActivePanel::Right => ACTIVE_COLOR,
-            ActivePanel::Left => INACTIVE_COLOR,
+        let placeholders: Placeholders = Placeholders {
+            cur_obj: self.get_cur_panel().get_cur_obj(),
+            selected: self.get_cur_panel().get_selected_objs(),
+            dir: self.get_cur_panel().get_path(),
+            other_dir: self.get_other_panel().get_path(),
         };
 
-        self.right_panel.render(panel_chunks[1], f, color);
+        let mut command: Command =
+            shell::get_shell_command(&placeholders.expand(text), &placeholders.dir);
 
-        let table: Table = Table::new(vec![
-            Row::new(vec![
-                format!["Search string: {}", self.search_str],
-                format!["F1 help"],
-            ]),
-            Row::new(vec![
-                format!["Active operations: {}", self.operations.len()],
-                format!["F2 copy"],
-            ]),
-            Row::new(vec![format![""], format!["F3 move"]]),
-            Row::new(vec![format![""], format!["F5 refresh"]]),
-            Row::new(vec![format![""], format!["F12 quit"]]),
-        ])
-        .style(Style::default().fg(Color::White))
-        .block(Block::default().title("Infos").borders(Borders::ALL))
-        .widths(&[Constraint::Percentage(50), Constraint::Percentage(50)]);
+        if is_interactive {
+            self.external_command = Some((command, true));
+            return;
+        }
 
-        f.render_widget(table, ui_chunks[1]);
+        let (sender, receiver) = mpsc::channel();
+        command.stdin(Stdio::null());
+
+        thread::spawn(move || {
+            let _ = sender.send(command.output());
+        });
+
+        self.running_command = Some((text.to_string(), receiver));
     }
 
-    pub fn thread_ctrl(&mut self) {
-        let mut finished_indexes: Vec<usize> = Vec::new();
+    fn poll_running_command(&mut self) {
+        let (text, result) = match self.running_command.as_ref() {
+            Some((text, receiver)) => match receiver.try_recv() {
+                Ok(x) => (text.clone(), x),
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    (text.clone(), Err(io::Error::other("The command got lost")))
+                }
+            },
+            None => return,
+        };
 
-        for index in 0..self.operations.len() {
-            if self.operations[index].is_finished() {
-                finished_indexes.push(index);
+        self.running_command = None;
+        self.refresh();
+
+        let output: Output = match result {
+            Ok(x) => x,
+            Err(error) => {
+                self.popup = Some(Popup::new(
+                    "Error",
+                    format!["Failed to run {} [Error: {}]", text, error],
+                    None,
+                ));
+                return;
             }
+        };
+
+        let mut message: String = format![
+            "{}{}",
+            String::from_utf8_lossy(&output.stdout),
+            String::from_utf8_lossy(&output.stderr)
+        ];
+
+        if message.trim().is_empty() {
+            message = String::from("The command finished without output\n");
         }
 
-        loop {
-            if finished_indexes.len() < 1 {
-                break;
-            }
+        if !output.status.success() {
+            message.push_str(&format!["\nThe command exited with {}", output.status]);
+        }
 
-            let index: usize = finished_indexes.remove(0);
-            finished_indexes = finished_indexes.iter().map(|x| x - 1).collect();
+        self.popup = Some(
+            Popup::new(
+                format!["Output of {} [Up/Down to scroll]", text],
+                message,
+                None,
+            )
+            .with_alignment(Alignment::Left),
+        );
+    }
 
-            let join_handle: JoinHandle<io::Result<()>> = self.operations.remove(index);
-            match join_handle.join().unwrap() {
-                Ok(_) => {}
-                Err(error) => {
+    pub fn clear_filter(&mut self) {
+        self.get_cur_panel().clear_filter();
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.get_cur_panel().toggle_hidden();
+    }
+
+    // Text pasted into the terminal goes to the focused input, the search
+    // otherwise
+    pub fn paste(&mut self, text: &str) {
+        if self.popup.is_some() {
+            return;
+        }
+
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.paste(text);
+        } else if let Some(form) = self.form.as_mut() {
+            form.paste(text);
+        } else if self.is_renaming() {
+            self.get_cur_panel().paste_rename(text);
+        } else if let Some(viewer) = self.viewer.as_mut() {
+            viewer.paste(text);
+        } else if !self.show_jobs {
+            self.search.paste(text);
+            self.jump_to_best_match();
+        }
+    }
+
+    pub fn edit_prompt(&mut self, key: KeyEvent) {
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.handle_key(key);
+        }
+    }
+
+    // Attaches the earlier inputs of the same kind of prompt
+    fn show_prompt(&mut self, prompt: Prompt) {
+        let history: &[String] = self
+            .prompt_history
+            .get(&prompt.get_kind())
+            .map(|x| x.as_slice())
+            .unwrap_or_default();
+
+        self.prompt = Some(prompt.with_history(history));
+    }
+
+    fn add_to_prompt_history(&mut self, prompt: &Prompt) {
+        add_to_history(
+            self.prompt_history.entry(prompt.get_kind()).or_default(),
+            prompt.get_input(),
+        );
+    }
+
+    pub fn cancel_prompt(&mut self) {
+        self.prompt = None;
+    }
+
+    pub fn confirm_prompt(&mut self) {
+        let prompt: Prompt = match self.prompt.take() {
+            Some(x) => x,
+            None => return,
+        };
+
+        self.add_to_prompt_history(&prompt);
+
+        match prompt.get_kind() {
+            PromptKind::MarkGlob => {
+                if let Err(error) = self.get_cur_panel().mark_matching(prompt.get_input()) {
                     self.popup = Some(Popup::new(
                         "Error",
-                        error,
-                        Some(Style::default().fg(Color::Red)),
+                        format![
+                            "Invalid glob pattern {} [Error: {}]",
+                            prompt.get_input(),
+                            error
+                        ],
+                        None,
                     ));
-                    return;
                 }
-            };
+            }
+            PromptKind::Filter => {
+                if let Err(error) = self.get_cur_panel().set_filter(prompt.get_input()) {
+                    self.popup = Some(Popup::new("Error", error, None));
+                }
+            }
+            PromptKind::Command => self.run_command_line(prompt.get_input()),
+            PromptKind::CreateDir => self.create_object(prompt.get_input(), true),
+            PromptKind::CreateFile => self.create_object(prompt.get_input(), false),
         }
     }
 
-    // 0 -> Source path
-    // 1 -> Destination path
-    fn get_copy_move_path(&mut self) -> (PathBuf, PathBuf) {
-        let src_path: PathBuf;
-        let mut dest_path: PathBuf;
+    pub fn open_find_form(&mut self) {
+        if self.get_cur_panel().is_trash() {
+            return;
+        }
 
-        src_path = self.get_cur_panel().get_cur_obj();
+        let path: PathBuf = self.get_cur_panel().get_path();
+        let kinds: Vec<&str> = FileKind::ALL.iter().map(|x| x.name()).collect();
 
-        if self.cur_panel == ActivePanel::Left {
-            // Copy from left to right panel
-            dest_path = self.right_panel.get_path();
-        } else {
-            // Copy from right to left panel
-            dest_path = self.left_panel.get_path();
+        self.form = Some(
+            Form::new(FormKind::Find, format!["Find in {}", path.display()])
+                .with_text("Name (glob or re:regex)", "")
+                .with_text("Min size (e.g. 10K)", "")
+                .with_text("Max size (e.g. 1.5M)", "")
+                .with_text("Modified after (e.g. 2024-01-31 or 7d)", "")
+                .with_text("Modified before", "")
+                .with_choice("Type", &kinds),
+        );
+    }
+
+    pub fn open_grep_form(&mut self) {
+        if self.get_cur_panel().is_trash() {
+            return;
         }
 
-        let file_name: &OsStr = src_path.file_name().unwrap();
-        dest_path.push(file_name);
+        let path: PathBuf = self.get_cur_panel().get_path();
 
-        return (src_path, dest_path);
+        self.form = Some(
+            Form::new(
+                FormKind::Grep,
+                format!["Search contents in {}", path.display()],
+            )
+            .with_text("Pattern", "")
+            .with_choice("Pattern is", &["literal", "regex"])
+            .with_choice("Case", &["sensitive", "insensitive"])
+            .with_text("Include files (e.g. *.rs *.toml)", "")
+            .with_text("Exclude files and folders (e.g. target .git)", ""),
+        );
     }
 
-    fn get_cur_panel(&mut self) -> &mut Panel {
-        if self.cur_panel == ActivePanel::Left {
-            return &mut self.left_panel;
-        } else {
-            return &mut self.right_panel;
+    // Several marked entries are renamed with a pattern, otherwise the name is
+    // edited in place
+    pub fn start_rename(&mut self) {
+        if self.get_cur_panel().is_trash() || self.get_cur_panel().is_results() {
+            return;
         }
-    }
-}
 
-fn copy_recursively(source: impl AsRef<Path>, destination: impl AsRef<Path>) -> io::Result<()> {
-    fs::create_dir_all(&destination)?;
+        let marked_count: usize = self.get_cur_panel().get_marked_count();
 
-    for entry in fs::read_dir(source)? {
-        let entry = entry?;
-        let filetype = entry.file_type()?;
-        if filetype.is_dir() {
-            copy_recursively(entry.path(), destination.as_ref().join(entry.file_name()))?;
-        } else {
-            fs::copy(entry.path(), destination.as_ref().join(entry.file_name()))?;
+        if marked_count < 2 {
+            self.get_cur_panel().start_rename();
+            return;
         }
+
+        let case_names: Vec<&str> = CaseConversion::ALL.iter().map(|x| x.name()).collect();
+
+        self.form = Some(
+            Form::new(
+                FormKind::Rename,
+                format![
+                    "Rename {} entries [$1 capture, {{n}} counter, {{n:3}} padded, {{date}}, {{date:%Y%m%d}}]",
+                    marked_count
+                ],
+            )
+            .with_text("Find (regex, empty for the whole name)", "")
+            .with_text("Replace with", "$0")
+            .with_choice("Case", &case_names)
+            .with_text("Counter start", "1")
+            .with_text("Counter step", "1"),
+        );
+    }
+
+    pub fn edit_rename(&mut self, key: KeyEvent) {
+        self.get_cur_panel().edit_rename(key);
     }
 
-    return Ok(());
+    pub fn cancel_rename(&mut self) {
+        self.get_cur_panel().cancel_rename();
+    }
+
+    pub fn confirm_rename(&mut self) {
+        let (old_path, name) = match self.get_cur_panel().take_rename() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let new_path: PathBuf = old_path.with_file_name(&name);
+
+        if new_path == old_path {
+            return;
+        }
+
+        let result: Result<(), String> = rename::check_name(&name).and_then(|_| {
+            if new_path.symlink_metadata().is_ok() && !rename::is_same_file(&old_path, &new_path) {
+                return Err(format!["{} already exists", new_path.display()]);
+            }
+
+            return fs::rename(&old_path, &new_path)
+                .map_err(|x| format!["Failed to rename {} [Error: {}]", old_path.display(), x]);
+        });
+
+        if let Err(error) = result {
+            self.popup = Some(Popup::new("Error", error, None));
+            return;
+        }
+
+        self.get_cur_panel().update_items();
+        self.get_cur_panel().select_obj(&new_path);
+    }
+
+    fn confirm_rename_form(&mut self, form: Form) {
+        let rule: Result<RenameRule, String> = RenameRule::new(
+            form.get_text(0),
+            form.get_text(1),
+            CaseConversion::ALL[form.get_choice(2)],
+            form.get_text(3),
+            form.get_text(4),
+        );
+
+        let plan: Result<Vec<(PathBuf, PathBuf)>, String> = rule.and_then(|x| {
+            let plan: Vec<(PathBuf, PathBuf)> =
+                x.plan(&self.get_cur_panel().get_selected_objs())?;
+
+            if plan.is_empty() {
+                return Err(String::from("None of the names change"));
+            }
+
+            rename::check_plan(&plan)?;
+            return Ok(plan);
+        });
+
+        let plan: Vec<(PathBuf, PathBuf)> = match plan {
+            Ok(x) => x,
+            Err(error) => {
+                self.popup = Some(Popup::new("Error", error, None));
+                self.form = Some(form);
+                return;
+            }
+        };
+
+        let preview: Vec<String> = plan
+            .iter()
+            .map(|(old, new)| {
+                format![
+                    "{} -> {}",
+                    old.file_name().unwrap_or_default().to_string_lossy(),
+                    new.file_name().unwrap_or_default().to_string_lossy()
+                ]
+            })
+            .collect();
+
+        self.popup = Some(
+            Popup::dialog(
+                "Rename [Up/Down to scroll]",
+                format!["Rename {} entries?\n\n{}", plan.len(), preview.join("\n")],
+                &[Button::Yes, Button::No],
+                PopupAction::Rename(plan),
+            )
+            .with_alignment(Alignment::Left),
+        );
+    }
+
+    fn rename_objects(&mut self, plan: Vec<(PathBuf, PathBuf)>) {
+        if let Err(error) = rename::rename_all(&plan) {
+            self.popup = Some(Popup::new("Error", error, None));
+        }
+
+        self.get_cur_panel().unmark_all();
+        self.get_cur_panel().update_items();
+
+        if let Some((_, new_path)) = plan.first() {
+            self.get_cur_panel().select_obj(new_path);
+        }
+    }
+
+    pub fn next_form_field(&mut self) {
+        if let Some(form) = self.form.as_mut() {
+            form.next_field();
+        }
+    }
+
+    pub fn previous_form_field(&mut self) {
+        if let Some(form) = self.form.as_mut() {
+            form.previous_field();
+        }
+    }
+
+    pub fn edit_form(&mut self, key: KeyEvent) {
+        if let Some(form) = self.form.as_mut() {
+            form.handle_key(key);
+        }
+    }
+
+    pub fn cancel_form(&mut self) {
+        self.form = None;
+    }
+
+    // Invalid input shows an error and keeps the form open
+    pub fn confirm_form(&mut self) {
+        let form: Form = match self.form.take() {
+            Some(x) => x,
+            None => return,
+        };
+
+        match form.get_kind() {
+            FormKind::Find => {
+                match FindCriteria::new(
+                    form.get_text(0),
+                    form.get_text(1),
+                    form.get_text(2),
+                    form.get_text(3),
+                    form.get_text(4),
+                    FileKind::ALL[form.get_choice(5)],
+                ) {
+                    Ok(criteria) => {
+                        self.search.clear();
+                        self.get_cur_panel().start_find(criteria);
+                    }
+                    Err(error) => {
+                        self.popup = Some(Popup::new("Error", error, None));
+                        self.form = Some(form);
+                    }
+                }
+            }
+            FormKind::Grep => {
+                match GrepCriteria::new(
+                    form.get_text(0),
+                    form.get_choice(1) == 1,
+                    form.get_choice(2) == 1,
+                    form.get_text(3),
+                    form.get_text(4),
+                ) {
+                    Ok(criteria) => {
+                        self.search.clear();
+                        self.get_cur_panel().start_find(criteria);
+                    }
+                    Err(error) => {
+                        self.popup = Some(Popup::new("Error", error, None));
+                        self.form = Some(form);
+                    }
+                }
+            }
+            FormKind::Rename => self.confirm_rename_form(form),
+            FormKind::Copy | FormKind::Move => self.confirm_destination_form(form),
+        }
+    }
+
+    pub fn open_help_popup(&mut self) {
+        self.popup = Some(Popup::new(
+            "Help",
+            format![
+                "{}Other chars - Search and jump to the first matching entry\n",
+                self.config.keymap.get_help_text()
+            ],
+            None,
+        ));
+    }
+
+    pub fn next_popup_button(&mut self) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.next_button();
+        }
+    }
+
+    pub fn previous_popup_button(&mut self) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.previous_button();
+        }
+    }
+
+    pub fn scroll_popup_up(&mut self, lines: u16) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.scroll_up(lines);
+        }
+    }
+
+    pub fn scroll_popup_down(&mut self, lines: u16) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.scroll_down(lines);
+        }
+    }
+
+    pub fn toggle_popup_option(&mut self) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.toggle_option();
+        }
+    }
+
+    pub fn press_selected_popup_button(&mut self) {
+        if let Some(popup) = self.popup.as_ref() {
+            self.press_popup_button(popup.get_selected_button());
+        }
+    }
+
+    pub fn press_escape_popup_button(&mut self) {
+        if let Some(popup) = self.popup.as_ref() {
+            self.press_popup_button(popup.get_escape_button());
+        }
+    }
+
+    pub fn press_popup_shortcut(&mut self, ch: char) {
+        if let Some(button) = self
+            .popup
+            .as_ref()
+            .and_then(|x| x.get_button_by_shortcut(ch))
+        {
+            self.press_popup_button(button);
+        }
+    }
+
+    // Closes the popup and executes its action depending on the button
+    fn press_popup_button(&mut self, button: Button) {
+        let mut popup: Popup = match self.popup.take() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let apply_to_all: bool = popup.is_option_checked();
+
+        match (popup.take_action(), button) {
+            (Some(PopupAction::Delete(paths)), Button::Yes) => self.trash_objects(paths),
+            (Some(PopupAction::PermanentDelete(paths)), Button::Yes) => {
+                self.spawn_delete_job(paths)
+            }
+            (Some(PopupAction::ResolveConflict), Button::Cancel) => {
+                self.jobs.cancel_conflicting();
+            }
+            (Some(PopupAction::ResolveConflict), button) => {
+                let action: ConflictAction = match button {
+                    Button::Overwrite => ConflictAction::Overwrite,
+                    Button::Rename => ConflictAction::Rename,
+                    Button::OverwriteIfNewer => ConflictAction::OverwriteIfNewer,
+                    _ => ConflictAction::Skip,
+                };
+
+                self.jobs.resolve_conflict(action, apply_to_all);
+            }
+            (Some(PopupAction::Restore(trash_items)), Button::Yes) => self.restore(trash_items),
+            (Some(PopupAction::RestoreTo(trash_items, dest_dir)), Button::Yes) => {
+                self.restore_to(trash_items, dest_dir)
+            }
+            (Some(PopupAction::Purge(trash_items)), Button::Yes) => self.purge(trash_items),
+            (Some(PopupAction::Rename(plan)), Button::Yes) => self.rename_objects(plan),
+            (Some(PopupAction::Quit), Button::Yes) => self.should_quit = true,
+            _ => {}
+        }
+    }
+
+    // Asks for confirmation if operations are still running
+    pub fn quit(&mut self) {
+        if self.jobs.is_empty() || !self.config.behavior.confirm_quit {
+            self.should_quit = true;
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Quit",
+            "Operations are still running. Cancel them and quit?",
+            &[Button::No, Button::Yes],
+            PopupAction::Quit,
+        ));
+    }
+
+    pub fn toggle_full_listing(&mut self) {
+        self.get_cur_panel().toggle_full_listing();
+    }
+
+    pub fn next_sort_key(&mut self) {
+        self.get_cur_panel().next_sort_key();
+    }
+
+    pub fn toggle_sort_order(&mut self) {
+        self.get_cur_panel().toggle_sort_order();
+    }
+
+    pub fn toggle_sort_case(&mut self) {
+        self.get_cur_panel().toggle_sort_case();
+    }
+
+    pub fn toggle_dirs_first(&mut self) {
+        self.get_cur_panel().toggle_dirs_first();
+    }
+
+    // Switches between recreating symlinks and copying their targets
+    pub fn toggle_dereference(&mut self) {
+        self.copy_options.dereference = !self.copy_options.dereference;
+    }
+
+    pub fn toggle_quick_view(&mut self) {
+        self.quick_view = !self.quick_view;
+    }
+
+    pub fn compute_dir_sizes(&mut self) {
+        self.get_cur_panel().compute_dir_sizes(false);
+    }
+
+    // Finds the largest entries of the folder
+    pub fn compute_all_dir_sizes(&mut self) {
+        self.get_cur_panel().compute_dir_sizes(true);
+        self.get_cur_panel().sort_by_size();
+    }
+
+    pub fn toggle_jobs_view(&mut self) {
+        self.show_jobs = !self.show_jobs;
+    }
+
+    pub fn next_job(&mut self) {
+        self.jobs.next();
+    }
+
+    pub fn previous_job(&mut self) {
+        self.jobs.previous();
+    }
+
+    pub fn cancel_job(&mut self) {
+        self.jobs.cancel_selected();
+    }
+
+    pub fn toggle_pause_job(&mut self) {
+        self.jobs.toggle_pause_selected();
+    }
+
+    // Cancels the running operations before sfmanager terminates
+    pub fn shutdown(&mut self) {
+        self.jobs.cancel_all();
+    }
+
+    pub fn copy_objects(&mut self) {
+        if self.is_trash_involved() || self.is_results_destination() {
+            return;
+        }
+
+        self.open_destination_form(FormKind::Copy);
+    }
+
+    fn spawn_copy_job(&mut self, src_dest_paths: Vec<(PathBuf, PathBuf)>) {
+        let description: String = get_job_description(&src_dest_paths);
+        let options: CopyOptions = self.copy_options;
+
+        self.jobs
+            .spawn(JobKind::Copy, description, move |context: &JobContext| {
+                for (src_path, _) in src_dest_paths.iter() {
+                    context.add_total(copy::get_total_size(src_path, options));
+                }
+
+                for (src_path, dest_path) in src_dest_paths {
+                    copy::copy_object(context, &src_path, &dest_path, options)?;
+                }
+
+                return Ok(());
+            });
+
+        self.get_cur_panel().unmark_all();
+    }
+
+    // The destination dialog counts as the confirmation of a move
+    pub fn move_objects(&mut self) {
+        if self.is_results_destination() {
+            return;
+        }
+
+        if self.get_cur_panel().is_trash() && !self.get_other_panel().is_trash() {
+            self.restore_trash_items_to_other_panel();
+            return;
+        }
+
+        if self.is_trash_involved() {
+            return;
+        }
+
+        if !self.config.behavior.confirm_move {
+            let dest_dir: PathBuf = self.get_other_panel().get_path();
+
+            match check_dest_paths(self.get_copy_move_paths(), &dest_dir, false) {
+                Ok(x) if !x.is_empty() => self.spawn_move_job(x),
+                Ok(_) => {}
+                Err(error) => self.popup = Some(Popup::new("Error", error, None)),
+            }
+            return;
+        }
+
+        self.open_destination_form(FormKind::Move);
+    }
+
+    // Pre-filled with the folder of the other panel, or the path in it for a
+    // single entry
+    fn open_destination_form(&mut self, kind: FormKind) {
+        let src_paths: Vec<PathBuf> = self.get_cur_panel().get_selected_objs();
+
+        let dest_path: PathBuf = match src_paths.as_slice() {
+            [] => return,
+            [src_path] => self
+                .get_other_panel()
+                .get_path()
+                .join(src_path.file_name().unwrap_or_default()),
+            _ => self.get_other_panel().get_path().join(""),
+        };
+
+        let verb: &str = match kind {
+            FormKind::Move => "Move",
+            _ => "Copy",
+        };
+
+        self.form = Some(
+            Form::new(
+                kind,
+                format![
+                    "{} {} [relative to the panel folder, a trailing / means into the folder]",
+                    verb,
+                    get_objs_description(&src_paths)
+                ],
+            )
+            .with_text("To", dest_path.display())
+            .with_completion(&self.get_cur_panel().get_path())
+            .with_history(&self.destination_history)
+            .with_choice("Missing folders", &["report", "create"])
+            .with_paths(src_paths),
+        );
+    }
+
+    // The sources are the ones named in the title of the form
+    fn confirm_destination_form(&mut self, form: Form) {
+        let base_dir: PathBuf = self.get_cur_panel().get_path();
+
+        let src_dest_paths: Vec<(PathBuf, PathBuf)> = match get_dest_paths(
+            form.get_paths(),
+            form.get_text(0),
+            &base_dir,
+            form.get_choice(1) == 1,
+        ) {
+            Ok(x) => x,
+            Err(error) => {
+                self.popup = Some(Popup::new("Error", error, None));
+                self.form = Some(form);
+                return;
+            }
+        };
+
+        add_to_history(&mut self.destination_history, form.get_text(0));
+
+        match form.get_kind() {
+            FormKind::Move => self.spawn_move_job(src_dest_paths),
+            _ => self.spawn_copy_job(src_dest_paths),
+        }
+    }
+
+    fn spawn_move_job(&mut self, src_dest_paths: Vec<(PathBuf, PathBuf)>) {
+        let description: String = get_job_description(&src_dest_paths);
+
+        self.jobs
+            .spawn(JobKind::Move, description, move |context: &JobContext| {
+                for (src_path, dest_path) in src_dest_paths {
+                    copy::move_object(context, &src_path, &dest_path)?;
+                }
+
+                return Ok(());
+            });
+
+        self.get_cur_panel().unmark_all();
+    }
+
+    pub fn refresh(&mut self) {
+        self.left_panel.update_items();
+        self.right_panel.update_items();
+    }
+
+    // Explicit refresh which also lists the trash again, checks all results and
+    // drops the calculated folder sizes
+    pub fn reload(&mut self) {
+        self.dir_sizes.clear();
+        self.reload_trash();
+        self.left_panel.reload_results();
+        self.right_panel.reload_results();
+        self.refresh();
+    }
+
+    fn reload_trash(&mut self) {
+        self.left_panel.reload_trash();
+        self.right_panel.reload_trash();
+    }
+
+    pub fn delete_objects(&mut self) {
+        if self.get_cur_panel().is_trash() {
+            let trash_items: Vec<TrashItem> = self.get_cur_panel().get_selected_trash_items();
+            self.open_purge_dialog(trash_items);
+            return;
+        }
+
+        let selected_objs: Vec<PathBuf> = self.get_cur_panel().get_selected_objs();
+
+        if selected_objs.is_empty() {
+            return;
+        }
+
+        if !self.config.behavior.confirm_delete {
+            self.trash_objects(selected_objs);
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Delete",
+            format![
+                "Move {} to the trash?",
+                get_objs_description(&selected_objs)
+            ],
+            &[Button::Yes, Button::No],
+            PopupAction::Delete(selected_objs),
+        ));
+    }
+
+    fn trash_objects(&mut self, paths: Vec<PathBuf>) {
+        let mut errors: Vec<String> = Vec::new();
+        let mut failed_paths: Vec<PathBuf> = Vec::new();
+
+        for obj in paths.into_iter() {
+            if let Err(error) = ::trash::delete(&obj) {
+                errors.push(format!["{} [Error: {}]", obj.display(), error]);
+                failed_paths.push(obj);
+            }
+        }
+
+        self.get_cur_panel().unmark_all();
+        self.reload_trash();
+
+        // Not every filesystem has a trash so offer to delete permanently instead
+        if !failed_paths.is_empty() {
+            self.popup = Some(Popup::dialog(
+                "Error",
+                format![
+                    "Failed to move to the trash:\n{}\n\nDelete permanently instead?",
+                    errors.join("\n")
+                ],
+                &[Button::No, Button::Yes],
+                PopupAction::PermanentDelete(failed_paths),
+            ));
+        }
+    }
+
+    pub fn delete_objects_permanently(&mut self) {
+        // Purges the whole trash
+        if self.get_cur_panel().is_trash() {
+            let trash_items: Vec<TrashItem> = self.get_cur_panel().get_trash_items();
+            self.open_purge_dialog(trash_items);
+            return;
+        }
+
+        let selected_objs: Vec<PathBuf> = self.get_cur_panel().get_selected_objs();
+
+        if selected_objs.is_empty() {
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Delete permanently",
+            format![
+                "Permanently delete {}?\nThis cannot be undone",
+                get_objs_description(&selected_objs)
+            ],
+            &[Button::No, Button::Yes],
+            PopupAction::PermanentDelete(selected_objs),
+        ));
+    }
+
+    fn spawn_delete_job(&mut self, paths: Vec<PathBuf>) {
+        let description: String = get_objs_description(&paths);
+
+        self.jobs
+            .spawn(JobKind::Delete, description, move |context: &JobContext| {
+                for path in paths.iter() {
+                    context.add_total(copy::get_total_size(path, CopyOptions::default()));
+                }
+
+                for path in paths.iter() {
+                    delete::remove_object(context, path)?;
+                }
+
+                return Ok(());
+            });
+
+        self.get_cur_panel().unmark_all();
+    }
+
+    pub fn toggle_trash(&mut self) {
+        self.search.clear();
+
+        if self.get_cur_panel().is_trash() {
+            self.get_cur_panel().close_trash();
+            return;
+        }
+
+        if let Err(error) = self.get_cur_panel().open_trash() {
+            self.popup = Some(Popup::new(
+                "Error",
+                format!["Failed to open the trash [Error: {}]", error],
+                None,
+            ));
+        }
+    }
+
+    // Restores the selected trash items to their original location
+    fn restore_trash_items(&mut self) {
+        let trash_items: Vec<TrashItem> = self.get_cur_panel().get_selected_trash_items();
+
+        if trash_items.is_empty() {
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Restore",
+            format![
+                "Restore {} to the original location?",
+                get_trash_items_description(&trash_items)
+            ],
+            &[Button::Yes, Button::No],
+            PopupAction::Restore(trash_items),
+        ));
+    }
+
+    fn restore_trash_items_to_other_panel(&mut self) {
+        let trash_items: Vec<TrashItem> = self.get_cur_panel().get_selected_trash_items();
+        let dest_dir: PathBuf = self.get_other_panel().get_path();
+
+        if trash_items.is_empty() {
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Restore",
+            format![
+                "Restore {} to {}?",
+                get_trash_items_description(&trash_items),
+                dest_dir.display()
+            ],
+            &[Button::Yes, Button::No],
+            PopupAction::RestoreTo(trash_items, dest_dir),
+        ));
+    }
+
+    fn open_purge_dialog(&mut self, trash_items: Vec<TrashItem>) {
+        if trash_items.is_empty() {
+            return;
+        }
+
+        self.popup = Some(Popup::dialog(
+            "Purge",
+            format![
+                "Permanently delete {} from the trash?\nThis cannot be undone",
+                get_trash_items_description(&trash_items)
+            ],
+            &[Button::No, Button::Yes],
+            PopupAction::Purge(trash_items),
+        ));
+    }
+
+    fn restore(&mut self, trash_items: Vec<TrashItem>) {
+        self.get_cur_panel().unmark_all();
+
+        if let Err(error) = trash::restore(trash_items) {
+            self.popup = Some(Popup::new(
+                "Error",
+                format!["Failed to restore [Error: {}]", error],
+                None,
+            ));
+        }
+
+        self.reload_trash();
+    }
+
+    // The items are restored to their original location first and moved afterwards
+    // since the trash only knows how to restore to the original location
+    fn restore_to(&mut self, trash_items: Vec<TrashItem>, dest_dir: PathBuf) {
+        let src_dest_paths: Vec<(PathBuf, PathBuf)> = trash_items
+            .iter()
+            .map(|x| (x.original_path(), dest_dir.join(&x.name)))
+            .collect();
+
+        self.restore(trash_items);
+
+        if self.popup.is_none() {
+            self.spawn_move_job(src_dest_paths);
+        }
+    }
+
+    fn purge(&mut self, trash_items: Vec<TrashItem>) {
+        self.get_cur_panel().unmark_all();
+
+        if let Err(error) = trash::purge(trash_items) {
+            self.popup = Some(Popup::new(
+                "Error",
+                format!["Failed to purge [Error: {}]", error],
+                None,
+            ));
+        }
+
+        self.reload_trash();
+    }
+
+    // Copying and moving from or into the trash is not possible
+    // Find results list entries of many directories so nothing can be put there
+    fn is_results_destination(&mut self) -> bool {
+        if self.get_other_panel().is_results() {
+            self.popup = Some(Popup::new(
+                "Error",
+                "Find results cannot be a destination, close them with Left first",
+                None,
+            ));
+            return true;
+        }
+
+        return false;
+    }
+
+    fn is_trash_involved(&mut self) -> bool {
+        if self.left_panel.is_trash() || self.right_panel.is_trash() {
+            self.popup = Some(Popup::new(
+                "Error",
+                "Use Enter or F3 to restore items and Delete to purge them",
+                None,
+            ));
+            return true;
+        }
+
+        return false;
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
+        if let Some(popup) = self.popup.as_mut() {
+            popup.render(f);
+            return;
+        }
+
+        if let Some(viewer) = self.viewer.as_mut() {
+            viewer.render(f);
+            return;
+        }
+
+        if self.show_jobs {
+            self.jobs
+                .render(f, &get_jobs_view_hints(&self.config.keymap));
+            return;
+        }
+
+        self.refresh();
+
+        let marked_count: usize = self.get_cur_panel().get_marked_count();
+        let cur_obj: PathBuf = self.get_cur_panel().get_cur_obj();
+        let dir_size: Option<SizeState> = self.get_cur_panel().get_dir_size(&cur_obj);
+        let symlink_mode: &str = match self.copy_options.dereference {
+            true => "copy targets",
+            false => "copy as links",
+        };
+
+        let ui_chunks: Vec<Rect> = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(85), Constraint::Percentage(15)].as_ref())
+            .split(f.size());
+
+        let panel_chunks: Vec<Rect> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)].as_ref())
+            .split(ui_chunks[0]);
+
+        let theme: &Theme = &self.config.theme;
+
+        let (left_color, left_search, right_color, right_search) = match self.cur_panel {
+            ActivePanel::Left => (theme.active, Some(&self.search), theme.inactive, None),
+            ActivePanel::Right => (theme.inactive, None, theme.active, Some(&self.search)),
+        };
+
+        if !self.quick_view || self.cur_panel == ActivePanel::Left {
+            self.left_panel
+                .render(panel_chunks[0], f, left_color, theme, left_search);
+        }
+
+        if !self.quick_view || self.cur_panel == ActivePanel::Right {
+            self.right_panel
+                .render(panel_chunks[1], f, right_color, theme, right_search);
+        }
+
+        if self.quick_view {
+            let (cur_obj, preview_chunk) = match self.cur_panel {
+                ActivePanel::Left => (self.left_panel.get_cur_obj(), panel_chunks[1]),
+                ActivePanel::Right => (self.right_panel.get_cur_obj(), panel_chunks[0]),
+            };
+
+            self.preview.update(&cur_obj);
+            self.preview.render(preview_chunk, f, theme.inactive);
+        }
+
+        let keymap: &KeyMap = &self.config.keymap;
+
+        let table: Table = Table::new(vec![
+            Row::new(vec![
+                Cell::from(Spans::from(
+                    [
+                        vec![Span::raw(format![
+                            "Search ({}): ",
+                            self.search.get_mode().name()
+                        ])],
+                        self.search.get_spans(Style::default()),
+                    ]
+                    .concat(),
+                )),
+                Cell::from(get_key_hint(keymap, Action::Help, "help")),
+            ]),
+            Row::new(vec![
+                self.jobs
+                    .get_summary(&get_key_hint(keymap, Action::Jobs, "details")),
+                get_key_hint(keymap, Action::Copy, "copy"),
+            ]),
+            Row::new(vec![
+                format!["Marked: {}", marked_count],
+                get_key_hint(keymap, Action::Move, "move"),
+            ]),
+            Row::new(vec![
+                format!["Symlinks: {}", symlink_mode],
+                get_key_hint(keymap, Action::Jobs, "operations"),
+            ]),
+            Row::new(vec![
+                match dir_size {
+                    Some(SizeState::Computing(x)) => format!["Size: {} so far", x.describe()],
+                    Some(SizeState::Done(x)) => format!["Size: {}", x.describe()],
+                    None => String::new(),
+                },
+                get_key_hint(keymap, Action::Refresh, "refresh"),
+            ]),
+            Row::new(vec![
+                match &self.running_command {
+                    Some((text, _)) => format!["Running: {}", text],
+                    None => String::new(),
+                },
+                get_key_hint(keymap, Action::Quit, "quit"),
+            ]),
+        ])
+        .style(Style::default().fg(Color::White))
+        .block(Block::default().title("Infos").borders(Borders::ALL))
+        .widths(&[Constraint::Percentage(50), Constraint::Percentage(50)]);
+
+        f.render_widget(table, ui_chunks[1]);
+
+        if let Some(prompt) = self.prompt.as_mut() {
+            prompt.render(f);
+        }
+
+        if let Some(form) = self.form.as_mut() {
+            form.render(f);
+        }
+    }
+
+    pub fn thread_ctrl(&mut self) {
+        // The output waits until the prompt is closed so that it does not cover a
+        // half typed command
+        if self.popup.is_none() && self.prompt.is_none() {
+            self.poll_running_command();
+        }
+
+        if self.dir_sizes.take_changed() {
+            self.left_panel.update_dir_sizes();
+            self.right_panel.update_dir_sizes();
+        }
+
+        self.job_errors.append(&mut self.jobs.poll());
+
+        // An open dialog keeps its pending action, the errors are shown afterwards
+        if self.popup.is_none() && !self.job_errors.is_empty() {
+            self.popup = Some(Popup::new(
+                "Error",
+                self.job_errors.join("\n"),
+                Some(Style::default().fg(Color::Red)),
+            ));
+            self.job_errors.clear();
+            return;
+        }
+
+        if self.popup.is_none() {
+            if let Some(conflict) = self.jobs.get_conflict() {
+                self.popup = Some(get_conflict_popup(conflict));
+            }
+        }
+    }
+
+    // 0 -> Source path
+    // 1 -> Destination path
+    fn get_copy_move_paths(&mut self) -> Vec<(PathBuf, PathBuf)> {
+        let src_paths: Vec<PathBuf> = self.get_cur_panel().get_selected_objs();
+        let dest_dir: PathBuf;
+
+        if self.cur_panel == ActivePanel::Left {
+            // Copy from left to right panel
+            dest_dir = self.right_panel.get_path();
+        } else {
+            // Copy from right to left panel
+            dest_dir = self.left_panel.get_path();
+        }
+
+        return src_paths
+            .into_iter()
+            .map(|src_path| {
+                let file_name: &OsStr = src_path.file_name().unwrap();
+                let dest_path: PathBuf = dest_dir.join(file_name);
+                (src_path, dest_path)
+            })
+            .collect();
+    }
+
+    fn get_cur_panel(&mut self) -> &mut Panel {
+        if self.cur_panel == ActivePanel::Left {
+            return &mut self.left_panel;
+        } else {
+            return &mut self.right_panel;
+        }
+    }
+
+    fn get_other_panel(&mut self) -> &mut Panel {
+        if self.cur_panel == ActivePanel::Left {
+            return &mut self.right_panel;
+        } else {
+            return &mut self.left_panel;
+        }
+    }
+}
+
+fn get_conflict_popup(conflict: &Conflict) -> Popup {
+    return Popup::dialog(
+        "Conflict",
+        format![
+            "The destination already exists\n\nSource: {}\nDestination: {}",
+            conflict.source.display(),
+            conflict.destination.display()
+        ],
+        &[
+            Button::Overwrite,
+            Button::Skip,
+            Button::Rename,
+            Button::OverwriteIfNewer,
+            Button::Cancel,
+        ],
+        PopupAction::ResolveConflict,
+    )
+    .with_option("Apply to all conflicts");
+}
+
+fn get_objs_description(paths: &[PathBuf]) -> String {
+    if paths.len() == 1 {
+        return paths[0].display().to_string();
+    }
+
+    return format!["{} objects", paths.len()];
+}
+
+// A destination which is a folder or ends with a separator receives the entries,
+// otherwise it is the new path of the single entry
+fn get_dest_paths(
+    src_paths: &[PathBuf],
+    input: &str,
+    base_dir: &Path,
+    create_dirs: bool,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    let input: &str = input.trim();
+
+    if input.is_empty() {
+        return Err(String::from("The destination must not be empty"));
+    }
+
+    let dest_path: PathBuf = base_dir.join(expand_tilde(input));
+    let is_into_dir: bool = src_paths.len() > 1
+        || input.ends_with('/')
+        || input.ends_with(std::path::MAIN_SEPARATOR)
+        || dest_path.is_dir();
+
+    let dest_dir: &Path = match is_into_dir {
+        true => &dest_path,
+        false => dest_path.parent().unwrap_or(base_dir),
+    };
+
+    let src_dest_paths: Vec<(PathBuf, PathBuf)> = match is_into_dir {
+        true => src_paths
+            .iter()
+            .map(|x| (x.clone(), dest_path.join(x.file_name().unwrap_or_default())))
+            .collect(),
+        false => vec![(src_paths[0].clone(), dest_path.clone())],
+    };
+
+    return check_dest_paths(src_dest_paths, dest_dir, create_dirs);
+}
+
+// Rejects folders copied or moved into themselves and makes sure the destination
+// folder exists
+fn check_dest_paths(
+    src_dest_paths: Vec<(PathBuf, PathBuf)>,
+    dest_dir: &Path,
+    create_dirs: bool,
+) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+    // A folder copied into itself would grow while it is copied
+    // Symlinks are copied as links, so only the folder of a source is resolved
+    for (src_path, dest_path) in src_dest_paths.iter() {
+        let src_path: PathBuf = match (src_path.parent(), src_path.file_name()) {
+            (Some(parent), Some(name)) => canonicalize_existing(parent).join(name),
+            _ => canonicalize_existing(src_path),
+        };
+
+        if canonicalize_existing(dest_path).starts_with(&src_path) {
+            return Err(format![
+                "{} cannot be copied or moved into itself",
+                src_path.display()
+            ]);
+        }
+    }
+
+    if !dest_dir.is_dir() {
+        if dest_dir.symlink_metadata().is_ok() {
+            return Err(format!["{} is no folder", dest_dir.display()]);
+        }
+
+        if !create_dirs {
+            return Err(format!["The folder {} does not exist", dest_dir.display()]);
+        }
+
+        fs::create_dir_all(dest_dir)
+            .map_err(|x| format!["Failed to create {} [Error: {}]", dest_dir.display(), x])?;
+    }
+
+    return Ok(src_dest_paths);
+}
+
+// Repeated inputs are stored once, the oldest inputs are dropped first
+fn add_to_history(history: &mut Vec<String>, input: &str) {
+    if input.is_empty() || history.last().map(|x| x.as_str()) == Some(input) {
+        return;
+    }
+
+    history.push(input.to_string());
+
+    if history.len() > MAX_HISTORY_LEN {
+        history.remove(0);
+    }
+}
+
+// Resolves symlinks in the part of the path which exists already
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing: &Path = path;
+    let mut missing: Vec<&OsStr> = Vec::new();
+
+    loop {
+        if let Ok(x) = fs::canonicalize(existing) {
+            return missing.iter().rev().fold(x, |path, name| path.join(name));
+        }
+
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+fn get_trash_items_description(trash_items: &[TrashItem]) -> String {
+    if trash_items.len() == 1 {
+        return trash_items[0].name.clone();
+    }
+
+    return format!["{} items", trash_items.len()];
+}
+
+fn get_job_description(src_dest_paths: &[(PathBuf, PathBuf)]) -> String {
+    if src_dest_paths.len() == 1 {
+        let (src_path, dest_path) = &src_dest_paths[0];
+        return format!["{} -> {}", src_path.display(), dest_path.display()];
+    }
+
+    return format!["{} objects", src_dest_paths.len()];
+}
+
+// Programs may be given with arguments, e.g. EDITOR="code --wait"
+fn get_env_command(vars: &[&str], default: &str) -> Command {
+    let text: String = vars
+        .iter()
+        .filter_map(|x| env::var(x).ok())
+        .find(|x| !x.trim().is_empty())
+        .unwrap_or_else(|| default.to_string());
+
+    let mut words = text.split_whitespace();
+    let mut command: Command = Command::new(words.next().unwrap_or(default));
+    command.args(words);
+
+    return command;
+}
+
+// First key bound to the action followed by the label, e.g. "F1 help"
+fn get_key_hint(keymap: &KeyMap, action: Action, label: &str) -> String {
+    return match keymap.get_keys(action).first() {
+        Some(key) => format!["{} {}", key, label],
+        None => format!["- {}", label],
+    };
+}
+
+// The operations view reuses the panel actions for its keys
+fn get_jobs_view_hints(keymap: &KeyMap) -> String {
+    let close_keys: Vec<String> = [Action::Jobs, Action::ClearSearch]
+        .iter()
+        .filter_map(|x| keymap.get_keys(*x).into_iter().next())
+        .collect();
+
+    return format![
+        "{}, {}, {}, {}, {} close",
+        get_key_hint(keymap, Action::Up, "previous"),
+        get_key_hint(keymap, Action::Down, "next"),
+        get_key_hint(keymap, Action::Delete, "cancel"),
+        get_key_hint(keymap, Action::ToggleMark, "pause/resume"),
+        match close_keys.is_empty() {
+            true => String::from("-"),
+            false => close_keys.join("/"),
+        }
+    ];
 }
@@ -0,0 +1,900 @@
+use std::{
+    collections::HashMap,
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use tui::style::Color;
+
+use super::panel::details::{Column, DEFAULT_COLUMNS};
+
+const CONFIG_DIR_NAME: &str = "sfmanager";
+const CONFIG_FILE_NAME: &str = "config.toml";
+
+// Everything which can be bound to a key in the panels
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Action {
+    Help,
+    Copy,
+    Move,
+    Jobs,
+    Refresh,
+    Quit,
+    Up,
+    Down,
+    Begin,
+    End,
+    EnterDir,
+    Open,
+    LeaveDir,
+    DeleteSearchChar,
+    SwitchPanel,
+    Delete,
+    DeletePermanently,
+    ToggleMark,
+    MarkAll,
+    UnmarkAll,
+    InvertMarks,
+    MarkGlob,
+    ToggleDereference,
+    ToggleTrash,
+    ToggleDetails,
+    NextSortKey,
+    ToggleSortOrder,
+    ToggleSortCase,
+    ToggleDirsFirst,
+    ToggleHidden,
+    Filter,
+    ClearFilter,
+    NextMatch,
+    PreviousMatch,
+    NextSearchMode,
+    Find,
+    Grep,
+    QuickView,
+    View,
+    Edit,
+    Pager,
+    CommandLine,
+    Rename,
+    CreateDir,
+    CreateFile,
+    DirSize,
+    DirSizesAll,
+    ClearSearch,
+}
+
+// Name used in the config file and description shown in the help
+// The order is used for the help popup
+const ACTIONS: &[(Action, &str, &str)] = &[
+    (Action::Help, "help", "Show this help"),
+    (Action::Copy, "copy", "Copy"),
+    (
+        Action::Move,
+        "move",
+        "Move, restore into the other panel in the trash",
+    ),
+    (Action::Jobs, "jobs", "Show or hide running operations"),
+    (Action::Refresh, "refresh", "Refresh"),
+    (Action::Quit, "quit", "Terminate sfmanager"),
+    (Action::Up, "up", "Go one entry up"),
+    (Action::Down, "down", "Go one entry down"),
+    (Action::Begin, "begin", "Go to the first entry"),
+    (Action::End, "end", "Go to the last entry"),
+    (
+        Action::EnterDir,
+        "enter_dir",
+        "Enter folder, go to the folder of a search result",
+    ),
+    (
+        Action::Open,
+        "open",
+        "Open file or enter folder, restore in the trash",
+    ),
+    (Action::LeaveDir, "leave_dir", "Leave folder"),
+    (
+        Action::DeleteSearchChar,
+        "delete_search_char",
+        "Delete last char from search string",
+    ),
+    (Action::SwitchPanel, "switch_panel", "Switch current panel"),
+    (
+        Action::Delete,
+        "delete",
+        "Move to the trash, purge in the trash, cancel an operation",
+    ),
+    (
+        Action::DeletePermanently,
+        "delete_permanently",
+        "Delete permanently, purge the whole trash in the trash",
+    ),
+    (
+        Action::ToggleMark,
+        "toggle_mark",
+        "Mark or unmark entry, pause or resume an operation",
+    ),
+    (Action::MarkAll, "mark_all", "Mark all entries"),
+    (Action::UnmarkAll, "unmark_all", "Unmark all entries"),
+    (Action::InvertMarks, "invert_marks", "Invert marks"),
+    (
+        Action::MarkGlob,
+        "mark_glob",
+        "Mark entries by glob pattern",
+    ),
+    (
+        Action::ToggleDereference,
+        "toggle_dereference",
+        "Toggle copying symlinks as links or their targets",
+    ),
+    (
+        Action::ToggleTrash,
+        "toggle_trash",
+        "Open or close the trash",
+    ),
+    (
+        Action::ToggleDetails,
+        "toggle_details",
+        "Switch between brief and full listing",
+    ),
+    (
+        Action::NextSortKey,
+        "next_sort_key",
+        "Sort by name, natural name, extension, size, mtime or ctime",
+    ),
+    (
+        Action::ToggleSortOrder,
+        "toggle_sort_order",
+        "Switch between ascending and descending order",
+    ),
+    (
+        Action::ToggleSortCase,
+        "toggle_sort_case",
+        "Toggle case-insensitive sorting",
+    ),
+    (
+        Action::ToggleDirsFirst,
+        "toggle_dirs_first",
+        "Toggle listing folders first",
+    ),
+    (
+        Action::ToggleHidden,
+        "toggle_hidden",
+        "Show or hide hidden files",
+    ),
+    (
+        Action::Filter,
+        "filter",
+        "Filter files by glob pattern or re:regex",
+    ),
+    (Action::ClearFilter, "clear_filter", "Clear filter"),
+    (
+        Action::NextMatch,
+        "next_match",
+        "Go to the next search match",
+    ),
+    (
+        Action::PreviousMatch,
+        "previous_match",
+        "Go to the previous search match",
+    ),
+    (
+        Action::NextSearchMode,
+        "next_search_mode",
+        "Search by substring, prefix, fuzzy or regex",
+    ),
+    (
+        Action::Find,
+        "find",
+        "Find files below the folder, Left closes the results",
+    ),
+    (
+        Action::Grep,
+        "grep",
+        "Search file contents below the folder, Left closes the results",
+    ),
+    (
+        Action::QuickView,
+        "quick_view",
+        "Preview the entry under the cursor in the other panel",
+    ),
+    (
+        Action::View,
+        "view",
+        "View the file with scrolling, search and hex mode",
+    ),
+    (Action::Edit, "edit", "Edit the file with $EDITOR"),
+    (Action::Pager, "pager", "View the file with $PAGER"),
+    (
+        Action::CommandLine,
+        "command_line",
+        "Run a shell command in the folder",
+    ),
+    (
+        Action::Rename,
+        "rename",
+        "Rename the entry in place, marked entries with a pattern",
+    ),
+    (
+        Action::CreateDir,
+        "create_dir",
+        "Create a folder, nested ones like a/b/c as well",
+    ),
+    (Action::CreateFile, "create_file", "Create an empty file"),
+    (
+        Action::DirSize,
+        "dir_size",
+        "Calculate the size of the selected folders",
+    ),
+    (
+        Action::DirSizesAll,
+        "dir_sizes_all",
+        "Calculate all folder sizes and sort by size",
+    ),
+    (
+        Action::ClearSearch,
+        "clear_search",
+        "Clear search string, close the operations view",
+    ),
+];
+
+const DEFAULT_BINDINGS: &[(&str, Action)] = &[
+    ("F1", Action::Help),
+    ("F2", Action::Copy),
+    ("F3", Action::Move),
+    ("F4", Action::Jobs),
+    ("F5", Action::Refresh),
+    ("F12", Action::Quit),
+    ("Up", Action::Up),
+    ("Down", Action::Down),
+    ("Home", Action::Begin),
+    ("End", Action::End),
+    ("Right", Action::EnterDir),
+    ("Enter", Action::Open),
+    ("Left", Action::LeaveDir),
+    ("Backspace", Action::DeleteSearchChar),
+    ("Tab", Action::SwitchPanel),
+    ("Delete", Action::Delete),
+    ("Shift+Delete", Action::DeletePermanently),
+    ("Insert", Action::ToggleMark),
+    ("Space", Action::ToggleMark),
+    ("Ctrl+A", Action::MarkAll),
+    ("Ctrl+U", Action::UnmarkAll),
+    ("Ctrl+R", Action::InvertMarks),
+    ("Ctrl+G", Action::MarkGlob),
+    ("Ctrl+L", Action::ToggleDereference),
+    ("Ctrl+T", Action::ToggleTrash),
+    ("Ctrl+D", Action::ToggleDetails),
+    ("Ctrl+S", Action::NextSortKey),
+    ("Ctrl+O", Action::ToggleSortOrder),
+    ("Alt+I", Action::ToggleSortCase),
+    ("Alt+D", Action::ToggleDirsFirst),
+    ("Alt+H", Action::ToggleHidden),
+    ("Ctrl+F", Action::Filter),
+    ("Alt+F", Action::ClearFilter),
+    ("Ctrl+N", Action::NextMatch),
+    ("Ctrl+P", Action::PreviousMatch),
+    ("Alt+S", Action::NextSearchMode),
+    ("Alt+F7", Action::Find),
+    ("Alt+G", Action::Grep),
+    ("Alt+V", Action::QuickView),
+    ("Alt+F3", Action::View),
+    ("Alt+E", Action::Edit),
+    ("Alt+P", Action::Pager),
+    ("Alt+C", Action::CommandLine),
+    ("F6", Action::Rename),
+    ("F7", Action::CreateDir),
+    ("Alt+N", Action::CreateFile),
+    ("Alt+Z", Action::DirSize),
+    ("Alt+A", Action::DirSizesAll),
+    ("Esc", Action::ClearSearch),
+];
+
+impl Action {
+    fn from_name(name: &str) -> Option<Action> {
+        return ACTIONS
+            .iter()
+            .find(|(_, x, _)| *x == name)
+            .map(|(action, _, _)| *action);
+    }
+
+    fn get_description(&self) -> &'static str {
+        return ACTIONS
+            .iter()
+            .find(|(x, _, _)| x == self)
+            .map(|(_, _, description)| *description)
+            .unwrap();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    // Parses bindings like "F2", "Ctrl+A" or "Shift+Delete"
+    fn parse(text: &str) -> Option<KeyBinding> {
+        let mut parts: Vec<&str> = text.split('+').collect();
+        let mut key: &str = parts.pop()?;
+        let mut modifiers: KeyModifiers = KeyModifiers::NONE;
+
+        // "Ctrl++" binds the plus key
+        if key.is_empty() && text.ends_with("++") {
+            parts.pop();
+            key = "+";
+        }
+
+        for modifier in parts {
+            modifiers |= match modifier.to_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let code: KeyCode = match key.to_lowercase().as_str() {
+            "enter" => KeyCode::Enter,
+            "esc" | "escape" => KeyCode::Esc,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "backspace" => KeyCode::Backspace,
+            "delete" | "del" => KeyCode::Delete,
+            "insert" | "ins" => KeyCode::Insert,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "pageup" => KeyCode::PageUp,
+            "pagedown" => KeyCode::PageDown,
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "space" => KeyCode::Char(' '),
+            x if x.len() > 1 && x.starts_with('f') => KeyCode::F(x[1..].parse().ok()?),
+            _ => {
+                let mut chars = key.chars();
+                let ch: char = chars.next()?;
+
+                if chars.next().is_some() {
+                    return None;
+                }
+
+                KeyCode::Char(ch)
+            }
+        };
+
+        return Some(Self::normalize(code, modifiers));
+    }
+
+    fn from_event(key: KeyEvent) -> KeyBinding {
+        return Self::normalize(key.code, key.modifiers);
+    }
+
+    // Terminals report shifted chars as upper case chars with or without the shift
+    // modifier and control chars in either case
+    fn normalize(code: KeyCode, mut modifiers: KeyModifiers) -> KeyBinding {
+        let mut code: KeyCode = code;
+
+        if let KeyCode::Char(ch) = code {
+            modifiers.remove(KeyModifiers::SHIFT);
+
+            if modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                code = KeyCode::Char(ch.to_ascii_lowercase());
+            }
+        }
+
+        return KeyBinding { code, modifiers };
+    }
+
+    fn is_plain_char(&self) -> bool {
+        return matches!(self.code, KeyCode::Char(_)) && self.modifiers.is_empty();
+    }
+
+    fn get_text(&self) -> String {
+        let mut text: String = String::new();
+
+        if self.modifiers.contains(KeyModifiers::CONTROL) {
+            text.push_str("Ctrl+");
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            text.push_str("Alt+");
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            text.push_str("Shift+");
+        }
+
+        let key: String = match self.code {
+            KeyCode::Char(' ') => String::from("Space"),
+            KeyCode::Char(x) if !self.modifiers.is_empty() => x.to_ascii_uppercase().to_string(),
+            KeyCode::Char(x) => x.to_string(),
+            KeyCode::F(x) => format!["F{}", x],
+            KeyCode::BackTab => String::from("BackTab"),
+            KeyCode::PageUp => String::from("PageUp"),
+            KeyCode::PageDown => String::from("PageDown"),
+            x => format!["{:?}", x],
+        };
+
+        text.push_str(&key);
+        return text;
+    }
+}
+
+pub struct KeyMap {
+    bindings: HashMap<KeyBinding, Action>,
+}
+
+impl KeyMap {
+    fn new(user_bindings: &HashMap<String, String>) -> Result<Self, String> {
+        let mut bindings: HashMap<KeyBinding, Action> = HashMap::new();
+
+        for (key, action) in DEFAULT_BINDINGS {
+            bindings.insert(KeyBinding::parse(key).unwrap(), *action);
+        }
+
+        for (key, action_name) in user_bindings.iter() {
+            let binding: KeyBinding = match KeyBinding::parse(key) {
+                Some(x) => x,
+                None => return Err(format!["Unknown key {}", key]),
+            };
+
+            // "none" removes a default binding
+            if action_name == "none" {
+                bindings.remove(&binding);
+                continue;
+            }
+
+            match Action::from_name(action_name) {
+                Some(action) => bindings.insert(binding, action),
+                None => return Err(format!["Unknown action {} for key {}", action_name, key]),
+            };
+        }
+
+        return Ok(KeyMap { bindings });
+    }
+
+    // Plain chars extend the search string while a search is in progress
+    pub fn get_action(&self, key: KeyEvent, is_searching: bool) -> Option<Action> {
+        let binding: KeyBinding = KeyBinding::from_event(key);
+
+        if is_searching && binding.is_plain_char() {
+            return None;
+        }
+
+        return self.bindings.get(&binding).copied();
+    }
+
+    // All keys bound to the action, sorted for a stable output
+    pub fn get_keys(&self, action: Action) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .bindings
+            .iter()
+            .filter(|(_, x)| **x == action)
+            .map(|(binding, _)| binding.get_text())
+            .collect();
+
+        keys.sort_by_key(|x| (x.len(), x.clone()));
+        return keys;
+    }
+
+    // Lists the bound keys of every action
+    pub fn get_help_text(&self) -> String {
+        let mut text: String = String::new();
+
+        for (action, _, _) in ACTIONS {
+            let keys: Vec<String> = self.get_keys(*action);
+
+            if keys.is_empty() {
+                continue;
+            }
+
+            text.push_str(&format![
+                "{} - {}\n",
+                keys.join("/"),
+                action.get_description()
+            ]);
+        }
+
+        return text;
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Theme {
+    pub active: Color,
+    pub inactive: Color,
+    pub marked: Color,
+    pub search_match: Color,
+    pub directory: Color,
+    pub file: Color,
+    pub image: Color,
+    pub audio: Color,
+    pub archive: Color,
+    pub video: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        return Theme {
+            active: Color::LightGreen,
+            inactive: Color::DarkGray,
+            marked: Color::Yellow,
+            search_match: Color::LightRed,
+            directory: Color::Blue,
+            file: Color::White,
+            image: Color::Magenta,
+            audio: Color::Cyan,
+            archive: Color::Red,
+            video: Color::Magenta,
+        };
+    }
+}
+
+impl Theme {
+    fn new(user_colors: &HashMap<String, String>) -> Result<Self, String> {
+        let mut theme: Theme = Theme::default();
+
+        for (name, value) in user_colors.iter() {
+            let color: Color = match parse_color(value) {
+                Some(x) => x,
+                None => return Err(format!["Unknown color {} for {}", value, name]),
+            };
+
+            match name.as_str() {
+                "active" => theme.active = color,
+                "inactive" => theme.inactive = color,
+                "marked" => theme.marked = color,
+                "search_match" => theme.search_match = color,
+                "directory" => theme.directory = color,
+                "file" => theme.file = color,
+                "image" => theme.image = color,
+                "audio" => theme.audio = color,
+                "archive" => theme.archive = color,
+                "video" => theme.video = color,
+                _ => return Err(format!["Unknown color entry {}", name]),
+            }
+        }
+
+        return Ok(theme);
+    }
+}
+
+// Accepts color names, "#rrggbb" and indexes of the 256 color palette
+fn parse_color(text: &str) -> Option<Color> {
+    if let Some(hex) = text.strip_prefix('#') {
+        if hex.len() != 6 {
+            return None;
+        }
+
+        let value: u32 = u32::from_str_radix(hex, 16).ok()?;
+        return Some(Color::Rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        ));
+    }
+
+    if let Ok(index) = text.parse::<u8>() {
+        return Some(Color::Indexed(index));
+    }
+
+    let color: Color = match text.to_lowercase().replace(['_', '-', ' '], "").as_str() {
+        "reset" => Color::Reset,
+        "black" => Color::Black,
+        "red" => Color::Red,
+        "green" => Color::Green,
+        "yellow" => Color::Yellow,
+        "blue" => Color::Blue,
+        "magenta" => Color::Magenta,
+        "cyan" => Color::Cyan,
+        "gray" | "grey" => Color::Gray,
+        "darkgray" | "darkgrey" => Color::DarkGray,
+        "lightred" => Color::LightRed,
+        "lightgreen" => Color::LightGreen,
+        "lightyellow" => Color::LightYellow,
+        "lightblue" => Color::LightBlue,
+        "lightmagenta" => Color::LightMagenta,
+        "lightcyan" => Color::LightCyan,
+        "white" => Color::White,
+        _ => return None,
+    };
+
+    return Some(color);
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Behavior {
+    // Copy the targets of symlinks instead of the symlinks
+    pub dereference_symlinks: bool,
+    // Ask before moving to the trash
+    pub confirm_delete: bool,
+    pub confirm_move: bool,
+    // Ask before quitting while operations are running
+    pub confirm_quit: bool,
+}
+
+impl Default for Behavior {
+    fn default() -> Self {
+        return Behavior {
+            dereference_symlinks: false,
+            confirm_delete: true,
+            confirm_move: true,
+            confirm_quit: true,
+        };
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct StartConfig {
+    left: Option<String>,
+    right: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct PanelConfigFile {
+    columns: Option<Vec<String>>,
+    full_listing: bool,
+    show_hidden: bool,
+}
+
+// Settings applied to both panels
+#[derive(Clone)]
+pub struct PanelConfig {
+    // Columns of the full listing next to the name
+    pub columns: Vec<Column>,
+    pub full_listing: bool,
+    pub show_hidden: bool,
+}
+
+impl Default for PanelConfig {
+    fn default() -> Self {
+        return PanelConfig {
+            columns: DEFAULT_COLUMNS.to_vec(),
+            full_listing: false,
+            show_hidden: false,
+        };
+    }
+}
+
+impl PanelConfig {
+    fn new(panel_config_file: &PanelConfigFile) -> Result<Self, String> {
+        let mut columns: Vec<Column> = DEFAULT_COLUMNS.to_vec();
+
+        if let Some(names) = &panel_config_file.columns {
+            columns.clear();
+
+            for name in names {
+                match Column::from_name(name) {
+                    Some(x) => columns.push(x),
+                    None => return Err(format!["Unknown column {}", name]),
+                }
+            }
+        }
+
+        return Ok(PanelConfig {
+            columns,
+            full_listing: panel_config_file.full_listing,
+            show_hidden: panel_config_file.show_hidden,
+        });
+    }
+}
+
+// Layout of the config file
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    keys: HashMap<String, String>,
+    colors: HashMap<String, String>,
+    start: StartConfig,
+    behavior: Behavior,
+    panel: PanelConfigFile,
+}
+
+pub struct Config {
+    pub keymap: KeyMap,
+    pub theme: Theme,
+    pub left_start: PathBuf,
+    pub right_start: PathBuf,
+    pub behavior: Behavior,
+    pub panel: PanelConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        return Config {
+            keymap: KeyMap::new(&HashMap::new()).unwrap(),
+            theme: Theme::default(),
+            left_start: get_home_path(),
+            right_start: get_home_path(),
+            behavior: Behavior::default(),
+            panel: PanelConfig::default(),
+        };
+    }
+}
+
+impl Config {
+    // Loads the config file from the config directory
+    // A missing config file results in the default config
+    pub fn load() -> Result<Self, String> {
+        let path: PathBuf = match get_config_path() {
+            Some(x) => x,
+            None => return Ok(Config::default()),
+        };
+
+        let text: String = match fs::read_to_string(&path) {
+            Ok(x) => x,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => {
+                return Err(format![
+                    "Failed to read config {} [Error: {}]",
+                    path.display(),
+                    error
+                ])
+            }
+        };
+
+        return Self::parse(&text)
+            .map_err(|error| format!["Invalid config {} [Error: {}]", path.display(), error]);
+    }
+
+    fn parse(text: &str) -> Result<Self, String> {
+        let config_file: ConfigFile = toml::from_str(text).map_err(|x| x.to_string())?;
+
+        return Ok(Config {
+            keymap: KeyMap::new(&config_file.keys)?,
+            theme: Theme::new(&config_file.colors)?,
+            left_start: get_start_path(config_file.start.left.as_deref()),
+            right_start: get_start_path(config_file.start.right.as_deref()),
+            behavior: config_file.behavior,
+            panel: PanelConfig::new(&config_file.panel)?,
+        });
+    }
+}
+
+pub fn get_home_path() -> PathBuf {
+    if cfg![windows] {
+        return PathBuf::from(format![
+            "{}{}",
+            env::var("HOMEDRIVE").unwrap_or_default(),
+            env::var("HOMEPATH").unwrap_or_default()
+        ]);
+    }
+
+    return PathBuf::from(env::var("HOME").unwrap_or_default());
+}
+
+// Replaces a leading "~" with the home path
+pub fn expand_tilde(path: &str) -> PathBuf {
+    if path == "~" {
+        return get_home_path();
+    }
+
+    if let Some(rest) = path.strip_prefix("~/") {
+        return get_home_path().join(rest);
+    }
+
+    return PathBuf::from(path);
+}
+
+// Falls back to the home path if the configured path is no directory
+fn get_start_path(path: Option<&str>) -> PathBuf {
+    if let Some(path) = path {
+        let path: PathBuf = expand_tilde(path);
+
+        if path.is_dir() {
+            return path;
+        }
+    }
+
+    return get_home_path();
+}
+
+// $XDG_CONFIG_HOME/sfmanager/config.toml, ~/.config/sfmanager/config.toml or
+// %APPDATA%\sfmanager\config.toml on Windows
+fn get_config_path() -> Option<PathBuf> {
+    let config_dir: PathBuf;
+
+    if cfg![windows] {
+        config_dir = PathBuf::from(env::var_os("APPDATA")?);
+    } else if let Some(xdg_config_home) = env::var_os("XDG_CONFIG_HOME") {
+        config_dir = PathBuf::from(xdg_config_home);
+    } else {
+        config_dir = Path::new(&env::var_os("HOME")?).join(".config");
+    }
+
+    return Some(config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn binding(code: KeyCode, modifiers: KeyModifiers) -> Option<KeyBinding> {
+        return Some(KeyBinding { code, modifiers });
+    }
+
+    #[test]
+    fn parses_modifiers() {
+        assert!(KeyBinding::parse("Ctrl+A") == binding(KeyCode::Char('a'), KeyModifiers::CONTROL));
+        assert!(
+            KeyBinding::parse("control+alt+x")
+                == binding(
+                    KeyCode::Char('x'),
+                    KeyModifiers::CONTROL | KeyModifiers::ALT
+                )
+        );
+        assert!(KeyBinding::parse("Shift+Delete") == binding(KeyCode::Delete, KeyModifiers::SHIFT));
+        assert!(KeyBinding::parse("Ctrl++") == binding(KeyCode::Char('+'), KeyModifiers::CONTROL));
+    }
+
+    #[test]
+    fn parses_named_keys() {
+        assert!(KeyBinding::parse("F12") == binding(KeyCode::F(12), KeyModifiers::NONE));
+        assert!(KeyBinding::parse("pageup") == binding(KeyCode::PageUp, KeyModifiers::NONE));
+        assert!(KeyBinding::parse("Space") == binding(KeyCode::Char(' '), KeyModifiers::NONE));
+        assert!(KeyBinding::parse("Esc") == binding(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(KeyBinding::parse("f") == binding(KeyCode::Char('f'), KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn rejects_invalid_keys() {
+        assert!(KeyBinding::parse("").is_none());
+        assert!(KeyBinding::parse("Hyper+A").is_none());
+        assert!(KeyBinding::parse("Ctrl+ab").is_none());
+        assert!(KeyBinding::parse("Fx").is_none());
+    }
+
+    #[test]
+    fn normalizes_shifted_chars() {
+        assert!(
+            KeyBinding::normalize(KeyCode::Char('A'), KeyModifiers::SHIFT)
+                == KeyBinding::normalize(KeyCode::Char('A'), KeyModifiers::NONE)
+        );
+        assert!(
+            KeyBinding::normalize(KeyCode::Char('A'), KeyModifiers::CONTROL)
+                == binding(KeyCode::Char('a'), KeyModifiers::CONTROL).unwrap()
+        );
+        assert_eq!(
+            KeyBinding::normalize(KeyCode::Char('a'), KeyModifiers::ALT).get_text(),
+            "Alt+A"
+        );
+    }
+
+    #[test]
+    fn parses_colors() {
+        assert_eq!(parse_color("#ff8000"), Some(Color::Rgb(255, 128, 0)));
+        assert_eq!(parse_color("Light_Green"), Some(Color::LightGreen));
+        assert_eq!(parse_color("dark grey"), Some(Color::DarkGray));
+        assert_eq!(parse_color("208"), Some(Color::Indexed(208)));
+        assert_eq!(parse_color("#fff"), None);
+        assert_eq!(parse_color("#gggggg"), None);
+        assert_eq!(parse_color("purple"), None);
+    }
+
+    #[test]
+    fn falls_back_to_defaults() {
+        let config: Config = Config::parse(
+            "[keys]\n\"Alt+Q\" = \"quit\"\n\"F2\" = \"none\"\n\n[behavior]\nconfirm_move = false\n",
+        )
+        .unwrap();
+
+        assert!(!config.behavior.confirm_move);
+        assert!(config.behavior.confirm_delete);
+        assert_eq!(config.keymap.get_keys(Action::Quit), ["F12", "Alt+Q"]);
+        assert!(config.keymap.get_keys(Action::Copy).is_empty());
+        assert_eq!(config.theme.active, Theme::default().active);
+        assert_eq!(config.panel.columns.len(), DEFAULT_COLUMNS.len());
+    }
+
+    #[test]
+    fn rejects_invalid_config() {
+        assert!(Config::parse("[behavior]\nconfirm_everything = true\n").is_err());
+        assert!(Config::parse("[keys]\n\"F2\" = \"fly\"\n").is_err());
+        assert!(Config::parse("[colors]\nactive = \"purple\"\n").is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::line_edit::LineEdit;
+
+const SELECTED_FIELD_COLOR: Color = Color::LightGreen;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FormKind {
+    Find,
+    Grep,
+    Rename,
+    Copy,
+    Move,
+}
+
+// Either a line of text or one of several choices
+struct Field {
+    label: String,
+    input: LineEdit,
+    choices: Vec<String>,
+    choice: usize,
+    // Paths typed into the field are completed with Tab relative to it
+    completion_dir: Option<PathBuf>,
+}
+
+impl Field {
+    fn is_choice(&self) -> bool {
+        return !self.choices.is_empty();
+    }
+}
+
+// Dialog with several labeled fields, the values are read by the app on Enter
+pub struct Form {
+    kind: FormKind,
+    title: String,
+    fields: Vec<Field>,
+    selected: usize,
+    // Entries the form acts on, taken when it was opened
+    paths: Vec<PathBuf>,
+}
+
+impl Form {
+    pub fn new(kind: FormKind, title: impl ToString) -> Self {
+        return Form {
+            kind,
+            title: title.to_string(),
+            fields: Vec::new(),
+            selected: 0,
+            paths: Vec::new(),
+        };
+    }
+
+    pub fn with_text(mut self, label: impl ToString, input: impl ToString) -> Self {
+        self.fields.push(Field {
+            label: label.to_string(),
+            input: LineEdit::new(input),
+            choices: Vec::new(),
+            choice: 0,
+            completion_dir: None,
+        });
+        return self;
+    }
+
+    // The first choice is selected by default
+    pub fn with_choice(mut self, label: impl ToString, choices: &[&str]) -> Self {
+        self.fields.push(Field {
+            label: label.to_string(),
+            input: LineEdit::new(""),
+            choices: choices.iter().map(|x| x.to_string()).collect(),
+            choice: 0,
+            completion_dir: None,
+        });
+        return self;
+    }
+
+    // Applies to the last added field
+    pub fn with_completion(mut self, dir: &Path) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.completion_dir = Some(dir.to_path_buf());
+        }
+        return self;
+    }
+
+    // Applies to the last added field, browsed with Ctrl+P and Ctrl+N
+    pub fn with_history(mut self, history: &[String]) -> Self {
+        if let Some(field) = self.fields.last_mut() {
+            field.input = LineEdit::new(field.input.get_text()).with_history(history);
+        }
+        return self;
+    }
+
+    pub fn with_paths(mut self, paths: Vec<PathBuf>) -> Self {
+        self.paths = paths;
+        return self;
+    }
+
+    pub fn get_paths(&self) -> &[PathBuf] {
+        return &self.paths;
+    }
+
+    pub fn get_kind(&self) -> FormKind {
+        return self.kind;
+    }
+
+    pub fn get_text(&self, index: usize) -> &str {
+        return self.fields[index].input.get_text();
+    }
+
+    pub fn get_choice(&self, index: usize) -> usize {
+        return self.fields[index].choice;
+    }
+
+    pub fn next_field(&mut self) {
+        self.selected = (self.selected + 1) % self.fields.len();
+    }
+
+    pub fn previous_field(&mut self) {
+        self.selected = (self.selected + self.fields.len() - 1) % self.fields.len();
+    }
+
+    // Left and Right pick a choice, the other keys edit text fields
+    // Tab completes paths in fields which support it and moves to the next field
+    // otherwise
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let field: &mut Field = &mut self.fields[self.selected];
+
+        if key.code == KeyCode::Tab {
+            match &field.completion_dir {
+                Some(dir) => field.input.complete_path(dir, false),
+                None => self.next_field(),
+            }
+            return;
+        }
+
+        if !field.is_choice() {
+            field.input.handle_key(key);
+            return;
+        }
+
+        match key.code {
+            KeyCode::Right => self.next_choice(),
+            KeyCode::Left => self.previous_choice(),
+            _ => {}
+        }
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        let field: &mut Field = &mut self.fields[self.selected];
+
+        if !field.is_choice() {
+            field.input.paste(text);
+        }
+    }
+
+    fn next_choice(&mut self) {
+        let field: &mut Field = &mut self.fields[self.selected];
+
+        if field.is_choice() {
+            field.choice = (field.choice + 1) % field.choices.len();
+        }
+    }
+
+    fn previous_choice(&mut self) {
+        let field: &mut Field = &mut self.fields[self.selected];
+
+        if field.is_choice() {
+            field.choice = (field.choice + field.choices.len() - 1) % field.choices.len();
+        }
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let height: u16 = self.fields.len() as u16 + 4;
+
+        let vertical_layout: Vec<Rect> = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(
+                [
+                    Constraint::Min(0),
+                    Constraint::Length(height),
+                    Constraint::Min(0),
+                ]
+                .as_ref(),
+            )
+            .split(f.size());
+
+        let form_layout: Vec<Rect> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints(
+                [
+                    Constraint::Percentage(20),
+                    Constraint::Percentage(60),
+                    Constraint::Percentage(20),
+                ]
+                .as_ref(),
+            )
+            .split(vertical_layout[1]);
+
+        let label_width: usize = self
+            .fields
+            .iter()
+            .map(|x| x.label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let mut lines: Vec<Spans> = Vec::new();
+
+        for (index, field) in self.fields.iter().enumerate() {
+            let is_selected: bool = index == self.selected;
+
+            let value: Vec<Span> = match (field.is_choice(), is_selected) {
+                (true, true) => vec![Span::raw(format!["< {} >", field.choices[field.choice]])],
+                (true, false) => vec![Span::raw(format!["  {}", field.choices[field.choice]])],
+                (false, true) => field.input.get_spans(Style::default()),
+                (false, false) => vec![Span::raw(field.input.get_text().to_string())],
+            };
+
+            let label_style: Style = match is_selected {
+                true => Style::default()
+                    .fg(SELECTED_FIELD_COLOR)
+                    .add_modifier(Modifier::BOLD),
+                false => Style::default(),
+            };
+
+            let mut spans: Vec<Span> = vec![Span::styled(
+                format!["{:width$} ", field.label, width = label_width],
+                label_style,
+            )];
+            spans.extend(value);
+            lines.push(Spans::from(spans));
+        }
+
+        lines.push(Spans::from(""));
+        lines.push(Spans::from(
+            "[Enter to confirm, Esc to cancel, Up/Down for the next field, Left/Right to choose, Tab completes]",
+        ));
+
+        let form_msg: Paragraph = Paragraph::new(Text::from(lines))
+            .block(
+                Block::default()
+                    .title(&self.title[..])
+                    .borders(Borders::ALL),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .alignment(Alignment::Left);
+
+        f.render_widget(Clear, form_layout[1]);
+        f.render_widget(form_msg, form_layout[1]);
+    }
+}
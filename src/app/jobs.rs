@@ -0,0 +1,547 @@
+use std::{
+    cell::Cell,
+    io,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, RecvTimeoutError, Sender},
+        Arc,
+    },
+    thread,
+    thread::JoinHandle,
+    time::{Duration, Instant},
+};
+
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    widgets::{Block, Borders, Clear, Gauge, Paragraph},
+    Frame,
+};
+
+pub mod conflict;
+use conflict::{Conflict, ConflictAction, ConflictReply};
+pub mod copy;
+pub mod delete;
+
+const GAUGE_COLOR: Color = Color::LightGreen;
+const PAUSED_COLOR: Color = Color::Yellow;
+const JOB_HEIGHT: u16 = 4;
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobKind {
+    Copy,
+    Move,
+    Delete,
+}
+
+impl JobKind {
+    fn name(&self) -> &'static str {
+        return match self {
+            JobKind::Copy => "Copy",
+            JobKind::Move => "Move",
+            JobKind::Delete => "Delete",
+        };
+    }
+}
+
+// Messages sent from a job thread to the ui
+pub enum JobEvent {
+    Total(u64),
+    Progress(u64),
+    File(PathBuf),
+    Conflict(Conflict),
+    // Problem with a single entry which got skipped without failing the job
+    Error(String),
+}
+
+// Flags shared between the ui and a job thread
+struct JobControl {
+    cancelled: AtomicBool,
+    paused: AtomicBool,
+}
+
+// Handed to the job thread to report its progress
+pub struct JobContext {
+    sender: Sender<JobEvent>,
+    control: Arc<JobControl>,
+    reply_receiver: Receiver<ConflictReply>,
+    conflict_action: Cell<Option<ConflictAction>>,
+}
+
+impl JobContext {
+    // Blocks while the job is paused
+    // Returns an error if the job got cancelled
+    pub fn check(&self) -> io::Result<()> {
+        while self.control.paused.load(Ordering::Relaxed) {
+            if self.control.cancelled.load(Ordering::Relaxed) {
+                break;
+            }
+
+            thread::sleep(PAUSE_POLL_INTERVAL);
+        }
+
+        if self.control.cancelled.load(Ordering::Relaxed) {
+            return Err(io::Error::new(
+                io::ErrorKind::Interrupted,
+                "Operation cancelled",
+            ));
+        }
+
+        return Ok(());
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        return self.control.cancelled.load(Ordering::Relaxed);
+    }
+
+    pub fn add_total(&self, bytes: u64) {
+        let _ = self.sender.send(JobEvent::Total(bytes));
+    }
+
+    pub fn add_progress(&self, bytes: u64) {
+        let _ = self.sender.send(JobEvent::Progress(bytes));
+    }
+
+    pub fn set_file(&self, file: PathBuf) {
+        let _ = self.sender.send(JobEvent::File(file));
+    }
+
+    pub fn report_error(&self, message: String) {
+        let _ = self.sender.send(JobEvent::Error(message));
+    }
+
+    // Asks the user how to handle an existing destination and blocks until the
+    // answer arrives, unless a previous answer was applied to all conflicts
+    pub fn get_conflict_action(
+        &self,
+        source: &Path,
+        destination: &Path,
+    ) -> io::Result<ConflictAction> {
+        if let Some(action) = self.conflict_action.get() {
+            return Ok(action);
+        }
+
+        let _ = self.sender.send(JobEvent::Conflict(Conflict {
+            source: source.to_path_buf(),
+            destination: destination.to_path_buf(),
+        }));
+
+        loop {
+            self.check()?;
+
+            match self.reply_receiver.recv_timeout(PAUSE_POLL_INTERVAL) {
+                Ok(reply) => {
+                    if reply.apply_to_all {
+                        self.conflict_action.set(Some(reply.action));
+                    }
+
+                    return Ok(reply.action);
+                }
+                Err(RecvTimeoutError::Timeout) => continue,
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(io::Error::other("Operation aborted"));
+                }
+            }
+        }
+    }
+}
+
+pub struct Job {
+    kind: JobKind,
+    description: String,
+    handle: Option<JoinHandle<io::Result<()>>>,
+    receiver: Receiver<JobEvent>,
+    control: Arc<JobControl>,
+    reply_sender: Sender<ConflictReply>,
+    conflict: Option<Conflict>,
+    errors: Vec<String>,
+    total_bytes: u64,
+    done_bytes: u64,
+    cur_file: PathBuf,
+    started: Instant,
+    paused_since: Option<Instant>,
+    paused_time: Duration,
+}
+
+impl Job {
+    fn is_cancelled(&self) -> bool {
+        return self.control.cancelled.load(Ordering::Relaxed);
+    }
+
+    fn cancel(&mut self) {
+        self.control.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    fn toggle_pause(&mut self) {
+        match self.paused_since.take() {
+            Some(paused_since) => {
+                self.paused_time += paused_since.elapsed();
+                self.control.paused.store(false, Ordering::Relaxed);
+            }
+            None => {
+                self.paused_since = Some(Instant::now());
+                self.control.paused.store(true, Ordering::Relaxed);
+            }
+        }
+    }
+
+    fn get_state(&self) -> &'static str {
+        if self.is_cancelled() {
+            return " [cancelling]";
+        } else if self.conflict.is_some() {
+            return " [waiting for conflict resolution]";
+        } else if self.paused_since.is_some() {
+            return " [paused]";
+        }
+
+        return "";
+    }
+
+    fn handle_events(&mut self) {
+        while let Ok(event) = self.receiver.try_recv() {
+            match event {
+                JobEvent::Total(bytes) => self.total_bytes += bytes,
+                JobEvent::Progress(bytes) => self.done_bytes += bytes,
+                JobEvent::File(file) => self.cur_file = file,
+                JobEvent::Conflict(conflict) => self.conflict = Some(conflict),
+                JobEvent::Error(message) => self.errors.push(message),
+            }
+        }
+    }
+
+    fn get_ratio(&self) -> f64 {
+        if self.total_bytes == 0 {
+            return 0.0;
+        }
+
+        return (self.done_bytes as f64 / self.total_bytes as f64).clamp(0.0, 1.0);
+    }
+
+    // Bytes per second since the job was started without the paused time
+    fn get_throughput(&self) -> f64 {
+        let mut paused_time: Duration = self.paused_time;
+
+        if let Some(paused_since) = self.paused_since {
+            paused_time += paused_since.elapsed();
+        }
+
+        let elapsed: f64 = self
+            .started
+            .elapsed()
+            .saturating_sub(paused_time)
+            .as_secs_f64();
+
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+
+        return self.done_bytes as f64 / elapsed;
+    }
+
+    fn get_eta(&self) -> Option<Duration> {
+        let throughput: f64 = self.get_throughput();
+
+        if throughput <= 0.0 || self.done_bytes > self.total_bytes {
+            return None;
+        }
+
+        let remaining: f64 = (self.total_bytes - self.done_bytes) as f64;
+        return Some(Duration::from_secs_f64(remaining / throughput));
+    }
+
+    fn get_label(&self) -> String {
+        let eta: String = match self.get_eta() {
+            Some(x) => format_duration(x),
+            None => String::from("--:--"),
+        };
+
+        return format![
+            "{:.0}% {}/{} {}/s ETA {}",
+            self.get_ratio() * 100.0,
+            format_size(self.done_bytes),
+            format_size(self.total_bytes),
+            format_size(self.get_throughput() as u64),
+            eta
+        ];
+    }
+}
+
+pub struct JobManager {
+    jobs: Vec<Job>,
+    selected: usize,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        return JobManager {
+            jobs: Vec::new(),
+            selected: 0,
+        };
+    }
+
+    pub fn spawn<F>(&mut self, kind: JobKind, description: impl ToString, work: F)
+    where
+        F: FnOnce(&JobContext) -> io::Result<()> + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::channel();
+        let (reply_sender, reply_receiver) = mpsc::channel();
+        let control: Arc<JobControl> = Arc::new(JobControl {
+            cancelled: AtomicBool::new(false),
+            paused: AtomicBool::new(false),
+        });
+        let context: JobContext = JobContext {
+            sender,
+            control: control.clone(),
+            reply_receiver,
+            conflict_action: Cell::new(None),
+        };
+
+        self.jobs.push(Job {
+            kind,
+            description: description.to_string(),
+            handle: Some(thread::spawn(move || work(&context))),
+            receiver,
+            control,
+            reply_sender,
+            conflict: None,
+            errors: Vec::new(),
+            total_bytes: 0,
+            done_bytes: 0,
+            cur_file: PathBuf::new(),
+            started: Instant::now(),
+            paused_since: None,
+            paused_time: Duration::ZERO,
+        });
+    }
+
+    pub fn next(&mut self) {
+        if self.selected + 1 < self.jobs.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.selected = self.selected.saturating_sub(1);
+    }
+
+    pub fn cancel_selected(&mut self) {
+        if let Some(job) = self.jobs.get_mut(self.selected) {
+            job.cancel();
+        }
+    }
+
+    pub fn toggle_pause_selected(&mut self) {
+        if let Some(job) = self.jobs.get_mut(self.selected) {
+            job.toggle_pause();
+        }
+    }
+
+    // Returns the first conflict which waits for an answer
+    pub fn get_conflict(&self) -> Option<&Conflict> {
+        return self.jobs.iter().find_map(|x| x.conflict.as_ref());
+    }
+
+    pub fn resolve_conflict(&mut self, action: ConflictAction, apply_to_all: bool) {
+        if let Some(job) = self.jobs.iter_mut().find(|x| x.conflict.is_some()) {
+            job.conflict = None;
+            let _ = job.reply_sender.send(ConflictReply {
+                action,
+                apply_to_all,
+            });
+        }
+    }
+
+    pub fn cancel_conflicting(&mut self) {
+        if let Some(job) = self.jobs.iter_mut().find(|x| x.conflict.is_some()) {
+            job.conflict = None;
+            job.cancel();
+        }
+    }
+
+    // Cancels all jobs and waits until their threads have cleaned up
+    pub fn cancel_all(&mut self) {
+        for job in self.jobs.iter_mut() {
+            job.cancel();
+        }
+
+        for job in self.jobs.iter_mut() {
+            if let Some(handle) = job.handle.take() {
+                let _ = handle.join();
+            }
+        }
+
+        self.jobs.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.jobs.is_empty();
+    }
+
+    // Processes the progress events and removes the finished jobs
+    // Returns the error messages of the failed jobs and the skipped entries
+    pub fn poll(&mut self) -> Vec<String> {
+        let mut errors: Vec<String> = Vec::new();
+
+        for job in self.jobs.iter_mut() {
+            job.handle_events();
+            errors.append(&mut job.errors);
+
+            let is_finished: bool = match &job.handle {
+                Some(handle) => handle.is_finished(),
+                None => true,
+            };
+
+            if !is_finished {
+                continue;
+            }
+
+            let result: io::Result<()> = match job.handle.take().unwrap().join() {
+                Ok(x) => x,
+                Err(_) => Err(io::Error::other("Job thread panicked")),
+            };
+
+            if job.is_cancelled() {
+                continue;
+            }
+
+            if let Err(error) = result {
+                errors.push(format![
+                    "{} {} failed [Error: {}]",
+                    job.kind.name(),
+                    job.description,
+                    error
+                ]);
+            }
+        }
+
+        self.jobs.retain(|x| x.handle.is_some());
+
+        if self.selected >= self.jobs.len() {
+            self.selected = self.jobs.len().saturating_sub(1);
+        }
+
+        return errors;
+    }
+
+    // Short summary of all jobs for the infos table
+    pub fn get_summary(&self, details_hint: &str) -> String {
+        if self.jobs.is_empty() {
+            return String::from("Active operations: 0");
+        }
+
+        let total_bytes: u64 = self.jobs.iter().map(|x| x.total_bytes).sum();
+        let done_bytes: u64 = self.jobs.iter().map(|x| x.done_bytes).sum();
+        let percent: u64 = (done_bytes.min(total_bytes) * 100)
+            .checked_div(total_bytes)
+            .unwrap_or(0);
+
+        return format![
+            "Active operations: {} ({}%, {})",
+            self.jobs.len(),
+            percent,
+            details_hint
+        ];
+    }
+
+    // The hints list the keys of the view
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>, hints: &str) {
+        let popup_layout: Vec<Rect> = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(100)].as_ref())
+            .margin(5)
+            .split(f.size());
+
+        let block: Block = Block::default()
+            .title(format!["Operations [{}]", hints])
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        let inner: Rect = block.inner(popup_layout[0]);
+
+        f.render_widget(Clear, popup_layout[0]);
+        f.render_widget(block, popup_layout[0]);
+
+        if self.jobs.is_empty() {
+            f.render_widget(Paragraph::new("No active operations"), inner);
+            return;
+        }
+
+        let mut constraints: Vec<Constraint> = self
+            .jobs
+            .iter()
+            .map(|_| Constraint::Length(JOB_HEIGHT))
+            .collect();
+        constraints.push(Constraint::Min(0));
+
+        let job_chunks: Vec<Rect> = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints(constraints)
+            .split(inner);
+
+        for (index, (job, chunk)) in self.jobs.iter().zip(job_chunks.iter()).enumerate() {
+            let job_layout: Vec<Rect> = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Length(3)].as_ref())
+                .split(*chunk);
+
+            let mut header_style: Style = Style::default();
+
+            if index == self.selected {
+                header_style = header_style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+            }
+
+            let header: Paragraph = Paragraph::new(format![
+                "{} {}{}: {}",
+                job.kind.name(),
+                job.description,
+                job.get_state(),
+                job.cur_file.display()
+            ])
+            .style(header_style);
+
+            let gauge_color: Color = match job.paused_since {
+                Some(_) => PAUSED_COLOR,
+                None => GAUGE_COLOR,
+            };
+
+            let gauge: Gauge = Gauge::default()
+                .block(Block::default().borders(Borders::ALL))
+                .gauge_style(Style::default().fg(gauge_color).bg(Color::Black))
+                .ratio(job.get_ratio())
+                .label(job.get_label());
+
+            f.render_widget(header, job_layout[0]);
+            f.render_widget(gauge, job_layout[1]);
+        }
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+
+    let mut size: f64 = bytes as f64;
+    let mut unit: usize = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        return format!["{} {}", bytes, UNITS[unit]];
+    }
+
+    return format!["{:.1} {}", size, UNITS[unit]];
+}
+
+fn format_duration(duration: Duration) -> String {
+    let secs: u64 = duration.as_secs();
+
+    if secs >= 3600 {
+        return format!["{}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60];
+    }
+
+    return format!["{:02}:{:02}", secs / 60, secs % 60];
+}
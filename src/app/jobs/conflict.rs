@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ConflictAction {
+    Overwrite,
+    Skip,
+    Rename,
+    OverwriteIfNewer,
+}
+
+// Answer of the user to a conflict
+#[derive(Clone, Copy)]
+pub struct ConflictReply {
+    pub action: ConflictAction,
+    pub apply_to_all: bool,
+}
+
+// Raised by a job if the destination already exists
+pub struct Conflict {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+}
@@ -0,0 +1,477 @@
+use std::{
+    ffi::OsString,
+    fs,
+    fs::File,
+    io,
+    io::{Read, Write},
+    path::{Path, PathBuf},
+};
+
+use super::{ConflictAction, JobContext};
+
+mod metadata;
+use metadata::DirId;
+
+const CHUNK_SIZE: usize = 1024 * 1024;
+
+#[derive(Clone, Copy, Default)]
+pub struct CopyOptions {
+    // Copy the targets of symlinks instead of recreating the symlinks
+    pub dereference: bool,
+}
+
+impl CopyOptions {
+    fn get_metadata(&self, path: &Path) -> io::Result<fs::Metadata> {
+        if self.dereference {
+            return fs::metadata(path);
+        }
+
+        return fs::symlink_metadata(path);
+    }
+}
+
+// Sums up the size of all files below the path the way a copy with the options
+// would see them
+// The size is only used for the progress, so unreadable entries are left out
+// and reported by the operation itself
+pub fn get_total_size(path: &Path, options: CopyOptions) -> u64 {
+    return sum_sizes(path, options, &mut Vec::new());
+}
+
+fn sum_sizes(path: &Path, options: CopyOptions, ancestors: &mut Vec<DirId>) -> u64 {
+    let metadata: fs::Metadata = match options.get_metadata(path) {
+        Ok(x) => x,
+        Err(_) => return 0,
+    };
+
+    if !metadata.is_dir() {
+        return metadata.len();
+    }
+
+    // Symlink loops are skipped by the copy as well
+    let dir_id: DirId = match metadata::get_dir_id(path, &metadata) {
+        Ok(x) => x,
+        Err(_) => return 0,
+    };
+
+    if ancestors.contains(&dir_id) {
+        return 0;
+    }
+
+    let entries: fs::ReadDir = match fs::read_dir(path) {
+        Ok(x) => x,
+        Err(_) => return 0,
+    };
+
+    ancestors.push(dir_id);
+
+    let total_size: u64 = entries
+        .filter_map(|x| x.ok())
+        .map(|x| sum_sizes(&x.path(), options, ancestors))
+        .sum();
+
+    ancestors.pop();
+    return total_size;
+}
+
+// Copies a file or directory and asks the user if the destination already exists
+// Returns the used destination or None if the object was skipped
+// A destination created by the copy is removed again if the job gets cancelled
+pub fn copy_object(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+) -> io::Result<Option<PathBuf>> {
+    return copy_entry(context, source, destination, options, &mut Vec::new());
+}
+
+// The ancestors hold the directories currently being copied to detect symlink loops
+fn copy_entry(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+    ancestors: &mut Vec<DirId>,
+) -> io::Result<Option<PathBuf>> {
+    let destination: PathBuf = match resolve_destination(context, source, destination, options)? {
+        Some(x) => x,
+        None => {
+            context.add_progress(get_total_size(source, options));
+            return Ok(None);
+        }
+    };
+
+    let is_created: bool = fs::symlink_metadata(&destination).is_err();
+    let result: io::Result<bool> = copy_resolved(context, source, &destination, options, ancestors);
+
+    if result.is_err() && is_created && context.is_cancelled() {
+        remove_partial(&destination);
+    }
+
+    return match result? {
+        true => Ok(Some(destination)),
+        false => Ok(None),
+    };
+}
+
+// Removes whatever a cancelled copy left behind without following symlinks
+fn remove_partial(path: &Path) {
+    let _ = match fs::symlink_metadata(path) {
+        Ok(x) if x.is_dir() => fs::remove_dir_all(path),
+        Ok(_) => fs::remove_file(path),
+        Err(_) => return,
+    };
+}
+
+// Recreates the source at the destination depending on its file type and applies
+// the metadata of the source afterwards
+// Returns false if the source was skipped, the reason is reported to the context
+fn copy_resolved(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+    ancestors: &mut Vec<DirId>,
+) -> io::Result<bool> {
+    let metadata: fs::Metadata = options.get_metadata(source)?;
+    let file_type: fs::FileType = metadata.file_type();
+
+    if file_type.is_dir() {
+        let dir_id: DirId = metadata::get_dir_id(source, &metadata)?;
+
+        if ancestors.contains(&dir_id) {
+            context.report_error(format![
+                "Skipped {} [Error: symlink loop to a parent folder]",
+                source.display()
+            ]);
+            return Ok(false);
+        }
+
+        ancestors.push(dir_id);
+        let result: io::Result<()> =
+            copy_recursively(context, source, destination, options, ancestors);
+        ancestors.pop();
+        result?;
+    } else if file_type.is_symlink() {
+        context.check()?;
+        context.set_file(source.to_path_buf());
+        metadata::create_symlink(&fs::read_link(source)?, destination)?;
+        context.add_progress(metadata.len());
+    } else if file_type.is_file() {
+        copy_file(context, source, destination)?;
+    } else {
+        context.check()?;
+        context.set_file(source.to_path_buf());
+
+        // Device files can only be created by privileged users
+        match metadata::create_special_file(destination, &metadata) {
+            Ok(()) => {}
+            Err(error) if error.kind() == io::ErrorKind::PermissionDenied => {
+                context.report_error(format!["Skipped {} [Error: {}]", source.display(), error]);
+                return Ok(false);
+            }
+            Err(error) => return Err(error),
+        }
+    }
+
+    metadata::preserve(source, destination, &metadata)?;
+    return Ok(true);
+}
+
+// Moves a file or directory and asks the user if the destination already exists
+// Returns the used destination or None if the object was skipped
+pub fn move_object(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+) -> io::Result<Option<PathBuf>> {
+    let options: CopyOptions = CopyOptions::default();
+    let destination: PathBuf = match resolve_destination(context, source, destination, options)? {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+
+    move_resolved(context, source, &destination)?;
+    return Ok(Some(destination));
+}
+
+// Renames the source if possible and falls back to copy and delete if the
+// destination is located on another filesystem
+// A rename counts as a single step of the progress, only copied files add their
+// size to the total of the job
+fn move_resolved(context: &JobContext, source: &Path, destination: &Path) -> io::Result<()> {
+    context.check()?;
+
+    let src_is_dir: bool = fs::symlink_metadata(source)?.is_dir();
+
+    // Merging into an existing directory has to be done entry by entry
+    if !(src_is_dir && destination.is_dir()) {
+        context.set_file(source.to_path_buf());
+
+        match fs::rename(source, destination) {
+            Ok(()) => {
+                context.add_total(1);
+                context.add_progress(1);
+                return Ok(());
+            }
+            Err(error) if error.kind() == io::ErrorKind::CrossesDevices => {}
+            Err(error) => return Err(error),
+        }
+    }
+
+    if !src_is_dir {
+        context.add_total(get_total_size(source, CopyOptions::default()));
+
+        // Skipped entries, e.g. device files, stay at the source
+        if !copy_resolved(
+            context,
+            source,
+            destination,
+            CopyOptions::default(),
+            &mut Vec::new(),
+        )? {
+            return Ok(());
+        }
+
+        verify_copy(source, destination)?;
+        fs::remove_file(source)?;
+        return Ok(());
+    }
+
+    let metadata: fs::Metadata = fs::symlink_metadata(source)?;
+    fs::create_dir_all(destination)?;
+
+    for entry in fs::read_dir(source)? {
+        let entry = entry?;
+        let entry_destination: PathBuf = destination.join(entry.file_name());
+
+        // Existing subdirectories are merged without asking
+        if entry.file_type()?.is_dir() && entry_destination.is_dir() {
+            move_resolved(context, &entry.path(), &entry_destination)?;
+        } else {
+            move_object(context, &entry.path(), &entry_destination)?;
+        }
+    }
+
+    metadata::preserve(source, destination, &metadata)?;
+
+    // Skipped entries keep the source directory alive
+    if fs::read_dir(source)?.next().is_none() {
+        fs::remove_dir(source)?;
+    }
+
+    return Ok(());
+}
+
+// Makes sure the destination is a complete copy before the source gets deleted
+fn verify_copy(source: &Path, destination: &Path) -> io::Result<()> {
+    let src_len: u64 = fs::symlink_metadata(source)?.len();
+    let dest_len: u64 = fs::symlink_metadata(destination)?.len();
+
+    if src_len != dest_len {
+        return Err(io::Error::other(format![
+            "Verification of {} failed [Expected {} bytes, found {} bytes]",
+            destination.display(),
+            src_len,
+            dest_len
+        ]));
+    }
+
+    return Ok(());
+}
+
+// Returns the destination to write to or None if the source should be skipped
+fn resolve_destination(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+) -> io::Result<Option<PathBuf>> {
+    let dest_metadata: fs::Metadata = match fs::symlink_metadata(destination) {
+        Ok(x) => x,
+        Err(_) => return Ok(Some(destination.to_path_buf())),
+    };
+
+    if is_same_entry(source, destination) {
+        return Err(io::Error::other(format![
+            "{} cannot be copied onto itself",
+            source.display()
+        ]));
+    }
+
+    let src_metadata: fs::Metadata = options.get_metadata(source)?;
+    let action: ConflictAction = context.get_conflict_action(source, destination)?;
+
+    let overwrite: bool = match action {
+        ConflictAction::Overwrite => true,
+        ConflictAction::Skip => false,
+        ConflictAction::Rename => return Ok(Some(get_free_path(destination))),
+        ConflictAction::OverwriteIfNewer => {
+            // Directories are merged so the files inside get compared one by one
+            (src_metadata.is_dir() && dest_metadata.is_dir())
+                || src_metadata.modified()? > dest_metadata.modified()?
+        }
+    };
+
+    if !overwrite {
+        return Ok(None);
+    }
+
+    // Directories are merged and regular files are truncated, everything else has to
+    // make room for the source
+    let is_replaceable: bool = match dest_metadata.is_dir() {
+        true => src_metadata.is_dir(),
+        false => src_metadata.is_file() && dest_metadata.is_file(),
+    };
+
+    if !is_replaceable && dest_metadata.is_dir() {
+        fs::remove_dir_all(destination)?;
+    } else if !is_replaceable {
+        fs::remove_file(destination)?;
+    }
+
+    return Ok(Some(destination.to_path_buf()));
+}
+
+// Checks whether both paths point to the same directory entry
+fn is_same_entry(source: &Path, destination: &Path) -> bool {
+    let src_parent: Option<PathBuf> = source.parent().and_then(|x| fs::canonicalize(x).ok());
+    let dest_parent: Option<PathBuf> = destination.parent().and_then(|x| fs::canonicalize(x).ok());
+
+    return src_parent.is_some()
+        && src_parent == dest_parent
+        && source.file_name() == destination.file_name();
+}
+
+// Appends a counter to the file stem until the path does not exist
+// e.g. "file.txt" -> "file (1).txt"
+fn get_free_path(path: &Path) -> PathBuf {
+    let stem: OsString = path.file_stem().unwrap_or_default().to_owned();
+    let extension: Option<OsString> = path.extension().map(|x| x.to_owned());
+    let mut counter: usize = 1;
+
+    loop {
+        let mut file_name: OsString = stem.clone();
+        file_name.push(format![" ({})", counter]);
+
+        if let Some(extension) = &extension {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+
+        let free_path: PathBuf = path.with_file_name(file_name);
+
+        if fs::symlink_metadata(&free_path).is_err() {
+            return free_path;
+        }
+
+        counter += 1;
+    }
+}
+
+fn copy_recursively(
+    context: &JobContext,
+    source: &Path,
+    destination: &Path,
+    options: CopyOptions,
+    ancestors: &mut Vec<DirId>,
+) -> io::Result<()> {
+    fs::create_dir_all(destination)?;
+
+    for entry in fs::read_dir(source)? {
+        context.check()?;
+
+        let entry = entry?;
+        let is_dir: bool = options.get_metadata(&entry.path())?.is_dir();
+        let entry_destination: PathBuf = destination.join(entry.file_name());
+
+        // Existing subdirectories are merged without asking
+        if is_dir && entry_destination.is_dir() {
+            copy_resolved(
+                context,
+                &entry.path(),
+                &entry_destination,
+                options,
+                ancestors,
+            )?;
+        } else {
+            copy_entry(
+                context,
+                &entry.path(),
+                &entry_destination,
+                options,
+                ancestors,
+            )?;
+        }
+    }
+
+    return Ok(());
+}
+
+// Copies the file in chunks and reports every chunk to the job context
+// An overwritten destination is kept if the job gets cancelled, only the partial
+// copy is removed
+fn copy_file(
+    context: &JobContext,
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+) -> io::Result<()> {
+    context.check()?;
+    context.set_file(source.as_ref().to_path_buf());
+
+    return copy_file_chunks(context, &source, &destination);
+}
+
+// Writes into a temporary file next to the destination which replaces the
+// destination only once it is complete, so other hard links of an overwritten
+// file and programs reading it never see a partial copy
+fn copy_file_chunks(
+    context: &JobContext,
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+) -> io::Result<()> {
+    let destination: &Path = destination.as_ref();
+    let mut temp_name: OsString =
+        OsString::from(format![".sfmanager-copy-{}-", std::process::id()]);
+    temp_name.push(destination.file_name().unwrap_or_default());
+    let temp: PathBuf = destination.with_file_name(temp_name);
+
+    let result: io::Result<()> =
+        write_chunks(context, source, &temp).and_then(|_| fs::rename(&temp, destination));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+
+    return result;
+}
+
+fn write_chunks(
+    context: &JobContext,
+    source: impl AsRef<Path>,
+    destination: impl AsRef<Path>,
+) -> io::Result<()> {
+    let mut src_file: File = File::open(&source)?;
+    let mut dest_file: File = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&destination)?;
+    let mut buffer: Vec<u8> = vec![0; CHUNK_SIZE];
+
+    loop {
+        context.check()?;
+
+        let read_bytes: usize = match src_file.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(x) => x,
+            Err(error) if error.kind() == io::ErrorKind::Interrupted => continue,
+            Err(error) => return Err(error),
+        };
+
+        dest_file.write_all(&buffer[..read_bytes])?;
+        context.add_progress(read_bytes as u64);
+    }
+
+    return Ok(());
+}
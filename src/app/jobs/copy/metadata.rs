@@ -0,0 +1,219 @@
+use std::{fs, io, path::Path};
+
+#[cfg(unix)]
+use std::{
+    ffi::CString,
+    os::unix::{ffi::OsStrExt, fs::MetadataExt},
+};
+
+// Applies ownership, extended attributes, permissions and timestamps of the source
+// to the destination
+// Ownership and extended attributes are only copied as far as permitted
+#[cfg(unix)]
+pub fn preserve(source: &Path, destination: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    let is_symlink: bool = metadata.file_type().is_symlink();
+
+    // Changing the owner may reset the setuid bits so it has to happen first
+    let _ = std::os::unix::fs::lchown(destination, Some(metadata.uid()), Some(metadata.gid()));
+
+    #[cfg(target_os = "linux")]
+    copy_xattrs(source, destination, !is_symlink);
+    #[cfg(not(target_os = "linux"))]
+    let _ = source;
+
+    // Symlinks have no permissions of their own
+    if !is_symlink {
+        fs::set_permissions(destination, metadata.permissions())?;
+    }
+
+    return set_times(destination, metadata);
+}
+
+#[cfg(not(unix))]
+pub fn preserve(_source: &Path, destination: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    if metadata.file_type().is_symlink() {
+        return Ok(());
+    }
+
+    if metadata.is_file() {
+        let dest_file: fs::File = fs::OpenOptions::new().write(true).open(destination)?;
+        dest_file.set_times(
+            fs::FileTimes::new()
+                .set_accessed(metadata.accessed()?)
+                .set_modified(metadata.modified()?),
+        )?;
+    }
+
+    return fs::set_permissions(destination, metadata.permissions());
+}
+
+// Identifies a directory independent of the path leading to it
+#[cfg(unix)]
+pub type DirId = (u64, u64);
+
+#[cfg(not(unix))]
+pub type DirId = std::path::PathBuf;
+
+#[cfg(unix)]
+pub fn get_dir_id(_path: &Path, metadata: &fs::Metadata) -> io::Result<DirId> {
+    return Ok((metadata.dev(), metadata.ino()));
+}
+
+#[cfg(not(unix))]
+pub fn get_dir_id(path: &Path, _metadata: &fs::Metadata) -> io::Result<DirId> {
+    return fs::canonicalize(path);
+}
+
+// Recreates fifos, sockets and device files
+#[cfg(unix)]
+pub fn create_special_file(destination: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    let path: CString = to_c_string(destination)?;
+
+    let result: libc::c_int = unsafe {
+        libc::mknod(
+            path.as_ptr(),
+            metadata.mode() as libc::mode_t,
+            metadata.rdev() as libc::dev_t,
+        )
+    };
+
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    return Ok(());
+}
+
+#[cfg(not(unix))]
+pub fn create_special_file(destination: &Path, _metadata: &fs::Metadata) -> io::Result<()> {
+    return Err(io::Error::other(format![
+        "{} is a special file which cannot be copied",
+        destination.display()
+    ]));
+}
+
+#[cfg(unix)]
+pub fn create_symlink(target: &Path, destination: &Path) -> io::Result<()> {
+    return std::os::unix::fs::symlink(target, destination);
+}
+
+#[cfg(windows)]
+pub fn create_symlink(target: &Path, destination: &Path) -> io::Result<()> {
+    if destination
+        .parent()
+        .unwrap_or(destination)
+        .join(target)
+        .is_dir()
+    {
+        return std::os::windows::fs::symlink_dir(target, destination);
+    }
+
+    return std::os::windows::fs::symlink_file(target, destination);
+}
+
+// Sets access and modification time without following symlinks
+#[cfg(unix)]
+fn set_times(destination: &Path, metadata: &fs::Metadata) -> io::Result<()> {
+    let path: CString = to_c_string(destination)?;
+    let times: [libc::timespec; 2] = [
+        libc::timespec {
+            tv_sec: metadata.atime() as libc::time_t,
+            tv_nsec: metadata.atime_nsec() as _,
+        },
+        libc::timespec {
+            tv_sec: metadata.mtime() as libc::time_t,
+            tv_nsec: metadata.mtime_nsec() as _,
+        },
+    ];
+
+    let result: libc::c_int = unsafe {
+        libc::utimensat(
+            libc::AT_FDCWD,
+            path.as_ptr(),
+            times.as_ptr(),
+            libc::AT_SYMLINK_NOFOLLOW,
+        )
+    };
+
+    if result != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    return Ok(());
+}
+
+// Copies all extended attributes which can be read from the source and written to
+// the destination, failures are ignored since not every filesystem supports them
+#[cfg(target_os = "linux")]
+fn copy_xattrs(source: &Path, destination: &Path, follow_source: bool) {
+    let (src_path, dest_path) = match (to_c_string(source), to_c_string(destination)) {
+        (Ok(x), Ok(y)) => (x, y),
+        _ => return,
+    };
+
+    let names: Vec<u8> = match read_xattr_buffer(|buffer, size| unsafe {
+        match follow_source {
+            true => libc::listxattr(src_path.as_ptr(), buffer, size),
+            false => libc::llistxattr(src_path.as_ptr(), buffer, size),
+        }
+    }) {
+        Some(x) => x,
+        None => return,
+    };
+
+    for name in names.split(|x| *x == 0).filter(|x| !x.is_empty()) {
+        let name: CString = match CString::new(name) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let value: Vec<u8> = match read_xattr_buffer(|buffer, size| unsafe {
+            match follow_source {
+                true => libc::getxattr(src_path.as_ptr(), name.as_ptr(), buffer as _, size),
+                false => libc::lgetxattr(src_path.as_ptr(), name.as_ptr(), buffer as _, size),
+            }
+        }) {
+            Some(x) => x,
+            None => continue,
+        };
+
+        unsafe {
+            libc::lsetxattr(
+                dest_path.as_ptr(),
+                name.as_ptr(),
+                value.as_ptr() as _,
+                value.len(),
+                0,
+            );
+        }
+    }
+}
+
+// Calls the xattr function once to query the size and once to fill the buffer
+#[cfg(target_os = "linux")]
+fn read_xattr_buffer<F>(read: F) -> Option<Vec<u8>>
+where
+    F: Fn(*mut libc::c_char, libc::size_t) -> libc::ssize_t,
+{
+    let size: libc::ssize_t = read(std::ptr::null_mut(), 0);
+
+    if size < 0 {
+        return None;
+    }
+
+    let mut buffer: Vec<u8> = vec![0; size as usize];
+    let size: libc::ssize_t = read(buffer.as_mut_ptr() as _, buffer.len());
+
+    if size < 0 {
+        return None;
+    }
+
+    buffer.truncate(size as usize);
+    return Some(buffer);
+}
+
+#[cfg(unix)]
+fn to_c_string(path: &Path) -> io::Result<CString> {
+    return CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| io::Error::other(format!["Invalid path {}", path.display()]));
+}
@@ -0,0 +1,25 @@
+use std::{fs, io, path::Path};
+
+use super::JobContext;
+
+// Removes a file or directory permanently and reports the freed bytes
+// Symlinks are removed themselves and never followed
+pub fn remove_object(context: &JobContext, path: &Path) -> io::Result<()> {
+    context.check()?;
+
+    let metadata: fs::Metadata = fs::symlink_metadata(path)?;
+
+    if !metadata.is_dir() {
+        context.set_file(path.to_path_buf());
+        fs::remove_file(path)?;
+        context.add_progress(metadata.len());
+        return Ok(());
+    }
+
+    for entry in fs::read_dir(path)? {
+        remove_object(context, &entry?.path())?;
+    }
+
+    context.set_file(path.to_path_buf());
+    return fs::remove_dir(path);
+}
@@ -0,0 +1,365 @@
+use std::{
+    fs,
+    path::{self, Path, PathBuf, MAIN_SEPARATOR},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{
+    style::{Modifier, Style},
+    text::Span,
+};
+
+use super::config::expand_tilde;
+
+// Single line of text with a cursor and readline like keys
+// Ctrl+W/U/K cut text which Ctrl+Y pastes again, Ctrl+P/N browse the history, text pasted into the terminal
+// arrives through paste
+pub struct LineEdit {
+    text: String,
+    // Char index, not byte index
+    cursor: usize,
+    cut_buffer: String,
+    // Older entries first
+    history: Vec<String>,
+    // Entry shown while browsing the history, the typed text is kept in draft
+    history_index: Option<usize>,
+    draft: String,
+}
+
+impl LineEdit {
+    pub fn new(text: impl ToString) -> Self {
+        let text: String = text.to_string();
+
+        return LineEdit {
+            cursor: text.chars().count(),
+            text,
+            cut_buffer: String::new(),
+            history: Vec::new(),
+            history_index: None,
+            draft: String::new(),
+        };
+    }
+
+    pub fn with_history(mut self, history: &[String]) -> Self {
+        self.history = history.to_vec();
+        return self;
+    }
+
+    pub fn get_text(&self) -> &str {
+        return &self.text;
+    }
+
+    pub fn set_text(&mut self, text: impl ToString) {
+        self.text = text.to_string();
+        self.cursor = self.text.chars().count();
+    }
+
+    // Keys which do not edit the text are ignored
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        let ctrl: bool = key.modifiers.contains(KeyModifiers::CONTROL);
+        let alt: bool = key.modifiers.contains(KeyModifiers::ALT);
+
+        match key.code {
+            KeyCode::Char('a') if ctrl => self.cursor = 0,
+            KeyCode::Char('e') if ctrl => self.cursor = self.len(),
+            KeyCode::Char('b') if alt => self.cursor = self.get_word_start(),
+            KeyCode::Char('f') if alt => self.cursor = self.get_word_end(),
+            KeyCode::Char('w') if ctrl => self.cut(self.get_word_start(), self.cursor),
+            KeyCode::Char('d') if alt => self.cut(self.cursor, self.get_word_end()),
+            KeyCode::Char('u') if ctrl => self.cut(0, self.cursor),
+            KeyCode::Char('k') if ctrl => self.cut(self.cursor, self.len()),
+            KeyCode::Char('y') if ctrl => self.insert_str(&self.cut_buffer.clone()),
+            KeyCode::Char('p') if ctrl => self.previous_history_entry(),
+            KeyCode::Char('n') if ctrl => self.next_history_entry(),
+            KeyCode::Char(_) if ctrl || alt => {}
+            KeyCode::Char(x) => self.insert_str(&x.to_string()),
+            KeyCode::Backspace if ctrl || alt => self.cut(self.get_word_start(), self.cursor),
+            KeyCode::Backspace if self.cursor > 0 => {
+                self.remove(self.cursor - 1, self.cursor);
+                self.cursor -= 1;
+            }
+            KeyCode::Delete => self.remove(self.cursor, (self.cursor + 1).min(self.len())),
+            KeyCode::Left if ctrl || alt => self.cursor = self.get_word_start(),
+            KeyCode::Right if ctrl || alt => self.cursor = self.get_word_end(),
+            KeyCode::Left => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Right => self.cursor = (self.cursor + 1).min(self.len()),
+            KeyCode::Home => self.cursor = 0,
+            KeyCode::End => self.cursor = self.len(),
+            _ => {}
+        }
+    }
+
+    // Line breaks of pasted text become spaces so that nothing gets confirmed
+    // by accident
+    pub fn paste(&mut self, text: &str) {
+        let text: String = text
+            .replace("\r\n", " ")
+            .chars()
+            .filter_map(|x| match x {
+                '\n' | '\r' | '\t' => Some(' '),
+                x if x.is_control() => None,
+                x => Some(x),
+            })
+            .collect();
+
+        self.insert_str(&text);
+    }
+
+    fn insert_str(&mut self, text: &str) {
+        let index: usize = self.get_byte_index(self.cursor);
+        self.text.insert_str(index, text);
+        self.cursor += text.chars().count();
+    }
+
+    pub fn previous_history_entry(&mut self) {
+        let index: usize = match self.history_index {
+            Some(0) => return,
+            Some(x) => x - 1,
+            None if self.history.is_empty() => return,
+            None => {
+                self.draft = self.text.clone();
+                self.history.len() - 1
+            }
+        };
+
+        self.history_index = Some(index);
+        self.set_text(self.history[index].clone());
+    }
+
+    pub fn next_history_entry(&mut self) {
+        let index: usize = match self.history_index {
+            Some(x) => x + 1,
+            None => return,
+        };
+
+        if index < self.history.len() {
+            self.history_index = Some(index);
+            self.set_text(self.history[index].clone());
+        } else {
+            self.history_index = None;
+            self.set_text(self.draft.clone());
+        }
+    }
+
+    // Completes the path before the cursor as far as it is unambiguous
+    // Relative paths start at the dir
+    // In a shell command only the last word is completed and special chars are
+    // escaped, otherwise the whole text before the cursor is the path
+    pub fn complete_path(&mut self, dir: &Path, is_shell: bool) {
+        let before_cursor: String = self.text.chars().take(self.cursor).collect();
+        let word: String = match is_shell {
+            true => unescape_shell_word(get_last_shell_word(&before_cursor)),
+            false => before_cursor,
+        };
+
+        // The typed part of the last component is completed
+        let (parent, prefix): (&str, &str) = match word.rfind(path::is_separator) {
+            Some(x) => word.split_at(x + 1),
+            None => ("", &word),
+        };
+
+        let parent_dir: PathBuf = dir.join(expand_tilde(parent));
+        let entries: fs::ReadDir = match fs::read_dir(parent_dir) {
+            Ok(x) => x,
+            Err(_) => return,
+        };
+
+        let candidates: Vec<String> = entries
+            .filter_map(|x| x.ok())
+            .filter_map(|x| {
+                let mut name: String = x.file_name().to_string_lossy().to_string();
+
+                if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.'))
+                {
+                    return None;
+                }
+
+                if x.path().is_dir() {
+                    name.push(MAIN_SEPARATOR);
+                }
+
+                Some(name)
+            })
+            .collect();
+
+        let completion: String = match candidates.len() {
+            0 => return,
+            1 => candidates[0].clone(),
+            _ => get_common_prefix(&candidates),
+        };
+
+        let completion: &str = &completion[prefix.len()..];
+
+        match is_shell {
+            true => self.insert_str(&escape_shell_word(completion)),
+            false => self.insert_str(completion),
+        }
+    }
+
+    // The char under the cursor is shown reversed
+    pub fn get_spans(&self, style: Style) -> Vec<Span<'static>> {
+        let before: String = self.text.chars().take(self.cursor).collect();
+        let cursor: String = self
+            .text
+            .chars()
+            .nth(self.cursor)
+            .unwrap_or(' ')
+            .to_string();
+        let after: String = self.text.chars().skip(self.cursor + 1).collect();
+
+        return vec![
+            Span::styled(before, style),
+            Span::styled(cursor, style.add_modifier(Modifier::REVERSED)),
+            Span::styled(after, style),
+        ];
+    }
+
+    fn len(&self) -> usize {
+        return self.text.chars().count();
+    }
+
+    fn get_byte_index(&self, cursor: usize) -> usize {
+        return self
+            .text
+            .char_indices()
+            .nth(cursor)
+            .map(|(x, _)| x)
+            .unwrap_or(self.text.len());
+    }
+
+    fn remove(&mut self, start: usize, end: usize) {
+        let start: usize = self.get_byte_index(start);
+        let end: usize = self.get_byte_index(end);
+        self.text.replace_range(start..end, "");
+    }
+
+    // Removes the chars between the char indices and keeps them for pasting
+    fn cut(&mut self, start: usize, end: usize) {
+        if start >= end {
+            return;
+        }
+
+        self.cut_buffer = self.text.chars().skip(start).take(end - start).collect();
+        self.remove(start, end);
+        self.cursor = start;
+    }
+
+    // Words are separated by whitespace and path separators
+    fn get_word_start(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index: usize = self.cursor;
+
+        while index > 0 && is_separator(chars[index - 1]) {
+            index -= 1;
+        }
+
+        while index > 0 && !is_separator(chars[index - 1]) {
+            index -= 1;
+        }
+
+        return index;
+    }
+
+    fn get_word_end(&self) -> usize {
+        let chars: Vec<char> = self.text.chars().collect();
+        let mut index: usize = self.cursor;
+
+        while index < chars.len() && is_separator(chars[index]) {
+            index += 1;
+        }
+
+        while index < chars.len() && !is_separator(chars[index]) {
+            index += 1;
+        }
+
+        return index;
+    }
+}
+
+fn is_separator(ch: char) -> bool {
+    return ch.is_whitespace() || path::is_separator(ch);
+}
+
+// Whitespace escaped by a backslash does not end a word
+#[cfg(unix)]
+fn get_last_shell_word(text: &str) -> &str {
+    let mut start: usize = 0;
+    let mut is_escaped: bool = false;
+
+    for (index, ch) in text.char_indices() {
+        if is_escaped {
+            is_escaped = false;
+        } else if ch == '\\' {
+            is_escaped = true;
+        } else if ch.is_whitespace() {
+            start = index + ch.len_utf8();
+        }
+    }
+
+    return &text[start..];
+}
+
+#[cfg(unix)]
+fn unescape_shell_word(word: &str) -> String {
+    let mut result: String = String::new();
+    let mut chars = word.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => result.extend(chars.next()),
+            x => result.push(x),
+        }
+    }
+
+    return result;
+}
+
+#[cfg(unix)]
+fn escape_shell_word(word: &str) -> String {
+    let mut result: String = String::new();
+
+    for ch in word.chars() {
+        if ch.is_whitespace() || "\\'\"$`&|;<>()*?[]{}#~!".contains(ch) {
+            result.push('\\');
+        }
+        result.push(ch);
+    }
+
+    return result;
+}
+
+// cmd has no escape char for spaces and backslashes separate paths there
+#[cfg(not(unix))]
+fn get_last_shell_word(text: &str) -> &str {
+    return match text.rfind(char::is_whitespace) {
+        Some(x) => &text[x + 1..],
+        None => text,
+    };
+}
+
+#[cfg(not(unix))]
+fn unescape_shell_word(word: &str) -> String {
+    return word.to_string();
+}
+
+#[cfg(not(unix))]
+fn escape_shell_word(word: &str) -> String {
+    return word.to_string();
+}
+
+fn get_common_prefix(texts: &[String]) -> String {
+    let mut prefix: String = texts[0].clone();
+
+    for text in &texts[1..] {
+        let len: usize = prefix
+            .char_indices()
+            .zip(text.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map(|((x, a), _)| x + a.len_utf8())
+            .unwrap_or(0);
+        prefix.truncate(len);
+    }
+
+    return prefix;
+}
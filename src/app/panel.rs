@@ -1,41 +1,205 @@
 use std::{
-    cmp::Ordering,
+    collections::{HashMap, HashSet},
     ffi::OsString,
     fs,
     fs::ReadDir,
     path::{Path, PathBuf},
 };
 
+use ::trash::TrashItem;
+use crossterm::event::KeyEvent;
+use glob::{Pattern, PatternError};
 use tui::{
     backend::Backend,
-    layout::Rect,
+    layout::{Constraint, Rect},
     style::{Color, Modifier, Style},
-    widgets::{Block, Borders, List, ListItem, ListState},
+    text::{Span, Spans},
+    widgets::{Block, Borders, Cell, Row, Table, TableState},
     Frame,
 };
 
+use super::{
+    config::{PanelConfig, Theme},
+    line_edit::LineEdit,
+};
+use details::{Column, Details, OwnerCache};
+use dir_size::{DirSizes, SizeState};
+use filter::Filter;
+use results::{Matcher, Results};
+use search::Search;
+use sort::{SortKey, SortMode};
+
 mod colors;
+pub mod details;
+pub mod dir_size;
+mod filter;
+pub mod find;
+pub mod grep;
+mod results;
+pub mod search;
+mod sort;
+pub mod trash;
 
 pub struct Panel {
-    state: ListState,
+    state: TableState,
     path: PathBuf,
     selection_history: Vec<usize>,
     items: Vec<PathBuf>,
+    // Metadata of the listed entries, read whenever the listing is updated
+    details: HashMap<PathBuf, Details>,
+    owners: OwnerCache,
+    columns: Vec<Column>,
+    full_listing: bool,
+    sort_mode: SortMode,
+    show_hidden: bool,
+    filter: Option<Filter>,
+    marked: HashSet<PathBuf>,
+    // Set while the panel shows the trash instead of the path
+    trash_items: Option<Vec<TrashItem>>,
+    // Set while the panel shows the results of a find instead of the path
+    results: Option<Results>,
+    // Entry being renamed and its new name typed over it
+    rename_input: Option<(PathBuf, LineEdit)>,
+    dir_sizes: DirSizes,
 }
 
 impl Panel {
-    pub fn new(path: &Path) -> Self {
+    pub fn new(path: &Path, config: &PanelConfig, dir_sizes: DirSizes) -> Self {
         let mut panel: Panel = Panel {
-            state: ListState::default(),
+            state: TableState::default(),
             path: path.to_path_buf(),
             selection_history: Vec::new(),
-            items: Self::get_items(path),
+            items: Vec::new(),
+            details: HashMap::new(),
+            owners: OwnerCache::default(),
+            columns: config.columns.clone(),
+            full_listing: config.full_listing,
+            sort_mode: SortMode::default(),
+            show_hidden: config.show_hidden,
+            filter: None,
+            marked: HashSet::new(),
+            trash_items: None,
+            results: None,
+            rename_input: None,
+            dir_sizes,
         };
 
+        panel.update_items();
         panel.begin();
         return panel;
     }
 
+    // Switches between the brief listing and the listing with detail columns
+    pub fn toggle_full_listing(&mut self) {
+        self.full_listing = !self.full_listing;
+    }
+
+    pub fn toggle_hidden(&mut self) {
+        self.show_hidden = !self.show_hidden;
+        self.update_items();
+    }
+
+    pub fn get_filter_text(&self) -> String {
+        return match &self.filter {
+            Some(x) => x.get_text().to_string(),
+            None => String::new(),
+        };
+    }
+
+    // Only files matching the filter are listed, an empty filter lists everything
+    pub fn set_filter(&mut self, text: &str) -> Result<(), String> {
+        self.filter = match text.is_empty() {
+            true => None,
+            false => Some(Filter::new(text)?),
+        };
+
+        self.update_items();
+        return Ok(());
+    }
+
+    pub fn clear_filter(&mut self) {
+        self.filter = None;
+        self.update_items();
+    }
+
+    pub fn next_sort_key(&mut self) {
+        self.sort_mode.key = self.sort_mode.key.next();
+        self.resort();
+    }
+
+    pub fn toggle_sort_order(&mut self) {
+        self.sort_mode.descending = !self.sort_mode.descending;
+        self.resort();
+    }
+
+    pub fn toggle_sort_case(&mut self) {
+        self.sort_mode.ignore_case = !self.sort_mode.ignore_case;
+        self.resort();
+    }
+
+    pub fn toggle_dirs_first(&mut self) {
+        self.sort_mode.dirs_first = !self.sort_mode.dirs_first;
+        self.resort();
+    }
+
+    // Sorts the listing again and keeps the cursor on the same entry
+    fn resort(&mut self) {
+        if self.is_trash() || self.is_results() {
+            return;
+        }
+
+        let cur_obj: PathBuf = self.get_cur_obj();
+        sort::sort(
+            &mut self.items,
+            &self.details,
+            &self.dir_sizes.get_apparent_sizes(),
+            self.sort_mode,
+        );
+        self.select_obj(&cur_obj);
+    }
+
+    // Calculates the selected folders, or all folders of the listing
+    pub fn compute_dir_sizes(&self, all: bool) {
+        if self.is_trash() {
+            return;
+        }
+
+        let objs: Vec<PathBuf> = match all {
+            true => self.items.clone(),
+            false => self.get_selected_objs(),
+        };
+
+        self.dir_sizes.compute(
+            objs.into_iter()
+                .filter(|x| self.details.get(x).map(|x| x.is_dir()).unwrap_or(false))
+                .collect(),
+        );
+    }
+
+    // Largest entries first to find what takes up the space
+    pub fn sort_by_size(&mut self) {
+        self.sort_mode.key = SortKey::Size;
+        self.sort_mode.descending = true;
+        self.resort();
+    }
+
+    // Called when calculated folder sizes changed
+    pub fn update_dir_sizes(&mut self) {
+        if self.sort_mode.key == SortKey::Size {
+            self.resort();
+        }
+    }
+
+    pub fn get_dir_size(&self, obj: &Path) -> Option<SizeState> {
+        return self.dir_sizes.get(obj);
+    }
+
+    pub fn select_obj(&mut self, obj: &Path) {
+        if let Some(index) = self.items.iter().position(|x| *x == obj) {
+            self.state.select(Some(index));
+        }
+    }
+
     pub fn get_cur_obj(&self) -> PathBuf {
         let selected_obj: usize = match self.state.selected() {
             Some(x) => x,
@@ -45,11 +209,239 @@ impl Panel {
         return self.items[selected_obj].clone();
     }
 
+    // Returns the marked objects in listing order or the object under the cursor
+    // if nothing is marked
+    pub fn get_selected_objs(&self) -> Vec<PathBuf> {
+        if self.marked.is_empty() {
+            return match self.state.selected() {
+                Some(x) => vec![self.items[x].clone()],
+                None => Vec::new(),
+            };
+        }
+
+        return self
+            .items
+            .iter()
+            .filter(|x| self.marked.contains(*x))
+            .cloned()
+            .collect();
+    }
+
+    pub fn get_marked_count(&self) -> usize {
+        return self.marked.len();
+    }
+
+    pub fn toggle_mark(&mut self) {
+        let selected_obj: usize = match self.state.selected() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let obj: &PathBuf = &self.items[selected_obj];
+
+        if !self.marked.remove(obj) {
+            self.marked.insert(obj.clone());
+        }
+
+        self.next();
+    }
+
+    pub fn mark_all(&mut self) {
+        self.marked = self.items.iter().cloned().collect();
+    }
+
+    pub fn unmark_all(&mut self) {
+        self.marked.clear();
+    }
+
+    pub fn invert_marks(&mut self) {
+        self.marked = self
+            .items
+            .iter()
+            .filter(|x| !self.marked.contains(*x))
+            .cloned()
+            .collect();
+    }
+
+    // Marks every entry whose file name matches the glob pattern
+    pub fn mark_matching(&mut self, pattern: &str) -> Result<(), PatternError> {
+        let pattern: Pattern = Pattern::new(pattern)?;
+
+        for obj in self.items.iter() {
+            if pattern.matches(&self.get_name(obj)) {
+                self.marked.insert(obj.clone());
+            }
+        }
+
+        return Ok(());
+    }
+
+    pub fn is_trash(&self) -> bool {
+        return self.trash_items.is_some();
+    }
+
+    pub fn open_trash(&mut self) -> Result<(), ::trash::Error> {
+        let trash_items: Vec<TrashItem> = trash::list()?;
+
+        self.results = None;
+        self.items = trash_items.iter().map(|x| PathBuf::from(&x.id)).collect();
+        self.trash_items = Some(trash_items);
+        self.marked.clear();
+        self.begin();
+        return Ok(());
+    }
+
+    // The trash is listed when it gets opened or reloaded and not on every update
+    pub fn reload_trash(&mut self) {
+        if let Some(trash_items) = self.trash_items.as_mut() {
+            *trash_items = trash::list().unwrap_or_default();
+            self.update_items();
+        }
+    }
+
+    pub fn close_trash(&mut self) {
+        self.trash_items = None;
+        self.marked.clear();
+        self.update_items();
+        self.begin();
+    }
+
+    pub fn is_results(&self) -> bool {
+        return self.results.is_some();
+    }
+
+    pub fn is_searching(&self) -> bool {
+        return self
+            .results
+            .as_ref()
+            .map(|x| x.is_running())
+            .unwrap_or(false);
+    }
+
+    // Lists the entries below the path accepted by the matcher, a running find is
+    // cancelled
+    pub fn start_find(&mut self, matcher: impl Matcher) {
+        self.trash_items = None;
+        self.results = Some(Results::start(&self.path, matcher));
+        self.items.clear();
+        self.details.clear();
+        self.marked.clear();
+        self.update_items();
+        self.begin();
+    }
+
+    // Reads the details of all results again
+    pub fn reload_results(&mut self) {
+        if let Some(results) = self.results.as_mut() {
+            results.uncheck_all();
+            self.items.clear();
+            self.details.clear();
+            self.update_items();
+        }
+    }
+
+    pub fn close_results(&mut self) {
+        self.results = None;
+        self.marked.clear();
+        self.update_items();
+        self.begin();
+    }
+
+    // Leaves the results and selects the result in its directory
+    pub fn jump_to_result(&mut self) {
+        let cur_obj: PathBuf = self.get_cur_obj();
+
+        let parent: &Path = match cur_obj.parent() {
+            Some(x) if self.is_results() => x,
+            _ => return,
+        };
+
+        self.path = parent.to_path_buf();
+        self.selection_history.clear();
+        self.close_results();
+        self.select_obj(&cur_obj);
+    }
+
+    // Returns the marked trash items or the one under the cursor if nothing is marked
+    pub fn get_selected_trash_items(&self) -> Vec<TrashItem> {
+        let trash_items: &Vec<TrashItem> = match &self.trash_items {
+            Some(x) => x,
+            None => return Vec::new(),
+        };
+
+        let selected_ids: HashSet<PathBuf> = self.get_selected_objs().into_iter().collect();
+
+        return trash_items
+            .iter()
+            .filter(|x| selected_ids.contains(Path::new(&x.id)))
+            .map(trash::clone_item)
+            .collect();
+    }
+
+    pub fn get_trash_items(&self) -> Vec<TrashItem> {
+        return match &self.trash_items {
+            Some(x) => x.iter().map(trash::clone_item).collect(),
+            None => Vec::new(),
+        };
+    }
+
+    // Name which is shown for the entry
+    fn get_name(&self, obj: &Path) -> String {
+        if let Some(trash_items) = &self.trash_items {
+            if let Some(item) = trash_items.iter().find(|x| Path::new(&x.id) == obj) {
+                return item.name.clone();
+            }
+        }
+
+        return get_file_name(obj);
+    }
+
+    pub fn is_renaming(&self) -> bool {
+        return self.rename_input.is_some();
+    }
+
+    // Starts editing the name of the entry under the cursor in place
+    pub fn start_rename(&mut self) {
+        if self.is_trash() || self.is_results() || self.state.selected().is_none() {
+            return;
+        }
+
+        let cur_obj: PathBuf = self.get_cur_obj();
+        self.rename_input = Some((cur_obj.clone(), LineEdit::new(get_file_name(&cur_obj))));
+    }
+
+    pub fn edit_rename(&mut self, key: KeyEvent) {
+        if let Some((_, input)) = self.rename_input.as_mut() {
+            input.handle_key(key);
+        }
+    }
+
+    pub fn paste_rename(&mut self, text: &str) {
+        if let Some((_, input)) = self.rename_input.as_mut() {
+            input.paste(text);
+        }
+    }
+
+    pub fn cancel_rename(&mut self) {
+        self.rename_input = None;
+    }
+
+    // Returns the entry the rename was started on and its new name
+    pub fn take_rename(&mut self) -> Option<(PathBuf, String)> {
+        let (obj, input): (PathBuf, LineEdit) = self.rename_input.take()?;
+        return Some((obj, input.get_text().to_string()));
+    }
+
     pub fn get_path(&self) -> PathBuf {
         return self.path.clone();
     }
 
     pub fn open_dir(&mut self) {
+        if self.is_results() {
+            self.jump_to_result();
+            return;
+        }
+
         let selected_dir: usize = match self.state.selected() {
             Some(x) => x,
             None => return,
@@ -58,6 +450,7 @@ impl Panel {
         if self.items[selected_dir].is_dir() {
             let dir_name: OsString = self.items[selected_dir].file_name().unwrap().to_owned();
             self.path.push(dir_name);
+            self.marked.clear();
             self.update_items();
             self.selection_history.push(selected_dir);
             self.begin();
@@ -65,7 +458,18 @@ impl Panel {
     }
 
     pub fn leave_dir(&mut self) {
+        if self.is_trash() {
+            self.close_trash();
+            return;
+        }
+
+        if self.is_results() {
+            self.close_results();
+            return;
+        }
+
         if self.path.pop() {
+            self.marked.clear();
             self.update_items();
             match self.selection_history.pop() {
                 Some(x) => self.state.select(Some(x)),
@@ -103,7 +507,7 @@ impl Panel {
     }
 
     pub fn begin(&mut self) {
-        if self.items.len() < 1 {
+        if self.items.is_empty() {
             self.state.select(None);
             return;
         }
@@ -112,7 +516,7 @@ impl Panel {
     }
 
     pub fn end(&mut self) {
-        if self.items.len() < 1 {
+        if self.items.is_empty() {
             self.state.select(None);
             return;
         }
@@ -120,71 +524,327 @@ impl Panel {
         self.state.select(Some(self.items.len() - 1))
     }
 
-    pub fn jump_to_first_matching(&mut self, search_str: &str) {
-        for (i, v) in self.items.iter().enumerate() {
-            let path_as_str: &str = v.file_name().unwrap().to_str().unwrap();
+    // Moves the cursor to the best match, the first one if all are equally good
+    pub fn jump_to_best_match(&mut self, search: &Search) {
+        let mut best: Option<(usize, i64)> = None;
 
-            if path_as_str.contains(search_str) {
-                self.state.select(Some(i));
-                return;
+        for (index, obj) in self.items.iter().enumerate() {
+            if let Some(found) = search.find(&self.get_name(obj)) {
+                if best.map(|(_, score)| found.score > score).unwrap_or(true) {
+                    best = Some((index, found.score));
+                }
             }
         }
+
+        if let Some((index, _)) = best {
+            self.state.select(Some(index));
+        }
     }
 
-    pub fn render<B: Backend>(&mut self, chunk: Rect, f: &mut Frame<B>, line_color: Color) {
-        let mut items: Vec<ListItem> = Vec::new();
+    // Moves the cursor to the next or previous match in listing order and wraps
+    // around at the ends
+    pub fn jump_to_next_match(&mut self, search: &Search, forward: bool) {
+        let matches: Vec<usize> = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, obj)| search.find(&self.get_name(obj)).is_some())
+            .map(|(index, _)| index)
+            .collect();
 
-        for obj in self.items.iter() {
-            let obj_color: Color = colors::get_color(obj);
+        let cur_index: usize = match self.state.selected() {
+            Some(x) => x,
+            None => return,
+        };
+
+        let next_index: Option<&usize> = match forward {
+            true => matches.iter().find(|x| **x > cur_index).or(matches.first()),
+            false => matches
+                .iter()
+                .rev()
+                .find(|x| **x < cur_index)
+                .or(matches.last()),
+        };
+
+        if let Some(index) = next_index {
+            self.state.select(Some(*index));
+        }
+    }
+
+    pub fn render<B: Backend>(
+        &mut self,
+        chunk: Rect,
+        f: &mut Frame<B>,
+        line_color: Color,
+        theme: &Theme,
+        search: Option<&Search>,
+    ) {
+        let columns: &[Column] = match self.full_listing && !self.is_trash() {
+            true => &self.columns,
+            false => &[],
+        };
+
+        let mut rows: Vec<Row> = Vec::new();
+
+        for (index, obj) in self.items.iter().enumerate() {
+            let mut file_name: String = match (&self.trash_items, &self.results) {
+                (Some(trash_items), _) => trash::format_item(&trash_items[index]),
+                (_, Some(results)) => results.get_display_name(obj),
+                _ => get_file_name(obj),
+            };
+
+            let style: Style;
+
+            // Search matches refer to the name which ends the path of results
+            let mut name_offset: usize = match self.is_results() {
+                true => file_name
+                    .chars()
+                    .count()
+                    .saturating_sub(self.get_name(obj).chars().count()),
+                false => 0,
+            };
+
+            if self.marked.contains(obj) {
+                file_name = format!["* {}", file_name];
+                name_offset += 2;
+                style = Style::default()
+                    .fg(theme.marked)
+                    .bg(Color::Black)
+                    .add_modifier(Modifier::BOLD);
+            } else {
+                let obj_color: Color = colors::get_color(obj, theme);
+                style = Style::default().fg(obj_color).bg(Color::Black);
+            }
 
-            items.push(
-                ListItem::new(obj.file_name().unwrap().to_str().unwrap().to_string())
-                    .style(Style::default().fg(obj_color).bg(Color::Black)),
+            let positions: Vec<usize> = search
+                .and_then(|x| x.find(&self.get_name(obj)))
+                .map(|x| x.positions)
+                .unwrap_or_default();
+
+            let mut name_spans: Spans = highlight(
+                &file_name,
+                &positions,
+                name_offset,
+                Style::default()
+                    .fg(theme.search_match)
+                    .add_modifier(Modifier::UNDERLINED),
             );
+
+            // Matching line of a content search
+            if let Some(preview) = self.results.as_ref().and_then(|x| x.get_preview(obj)) {
+                name_spans.0.push(Span::styled(
+                    format!["  {}", preview],
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+
+            if let Some((_, input)) = self.rename_input.as_ref().filter(|(x, _)| x == obj) {
+                name_spans = Spans::from(
+                    input.get_spans(Style::default().fg(Color::Black).bg(Color::White)),
+                );
+            }
+
+            let dir_size: Option<SizeState> = self.dir_sizes.get(obj);
+
+            // The brief listing shows calculated folder sizes after the name
+            if let (Some(size), true) = (dir_size, columns.is_empty()) {
+                name_spans.0.push(Span::styled(
+                    format!["  [{}]", size.get_column()],
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+
+            let mut cells: Vec<Cell> = vec![Cell::from(name_spans)];
+
+            for column in columns {
+                cells.push(Cell::from(match (self.details.get(obj), dir_size) {
+                    (Some(details), Some(size)) if *column == Column::Size && details.is_dir() => {
+                        size.get_column()
+                    }
+                    (Some(details), _) => details.get_column(*column),
+                    (None, _) => String::new(),
+                }));
+            }
+
+            rows.push(Row::new(cells).style(style));
         }
 
-        let items = List::new(items)
+        let mut title: String = match (self.is_trash(), &self.results) {
+            (true, _) => String::from("Trash"),
+            (_, Some(results)) => format![
+                "Results in {} [{} found{}]",
+                results.get_root().display(),
+                self.items.len(),
+                match results.is_running() {
+                    true => ", searching",
+                    false => "",
+                }
+            ],
+            _ => format!["{} [{}]", self.path.display(), self.sort_mode.describe()],
+        };
+
+        if let (Some(filter), false) = (&self.filter, self.is_results()) {
+            title = format!["{} [filter: {}]", title, filter.get_text()];
+        }
+
+        if self.show_hidden && !self.is_trash() && !self.is_results() {
+            title = format!["{} [hidden shown]", title];
+        }
+
+        if !self.marked.is_empty() {
+            title = format!["{} [{} marked]", title, self.marked.len()];
+        }
+
+        // The name column takes the space left by the detail columns
+        let table_width: u16 = chunk.width.saturating_sub(2);
+        let column_widths: Vec<u16> = columns.iter().map(|x| x.get_width(table_width)).collect();
+        let name_width: u16 = table_width
+            .saturating_sub(column_widths.iter().sum::<u16>() + column_widths.len() as u16);
+
+        let mut widths: Vec<Constraint> = vec![Constraint::Length(name_width)];
+        widths.extend(column_widths.into_iter().map(Constraint::Length));
+
+        let mut table: Table = Table::new(rows)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(self.path.to_str().unwrap().to_owned())
+                    .title(title)
                     .border_style(Style::default().fg(line_color)),
             )
+            .widths(&widths)
+            .column_spacing(1)
             .highlight_style(Style::default().bg(line_color).add_modifier(Modifier::BOLD));
 
-        f.render_stateful_widget(items, chunk, &mut self.state);
+        if !columns.is_empty() {
+            let mut titles: Vec<&str> = vec!["Name"];
+            titles.extend(columns.iter().map(|x| x.get_title()));
+
+            table = table.header(
+                Row::new(titles)
+                    .style(Style::default().fg(line_color).add_modifier(Modifier::BOLD)),
+            );
+        }
+
+        f.render_stateful_widget(table, chunk, &mut self.state);
     }
 
     pub fn update_items(&mut self) {
-        self.items = Self::get_items(&self.path);
+        let cur_obj: PathBuf = self.get_cur_obj();
+
+        if let Some(trash_items) = &self.trash_items {
+            self.items = trash_items.iter().map(|x| PathBuf::from(&x.id)).collect();
+            self.details.clear();
+        } else if let Some(results) = self.results.as_mut() {
+            results.update();
+
+            // Only new results are read, entries which were deleted or moved in the
+            // meantime are left out once the results get reloaded
+            for path in results.take_unchecked() {
+                if let Some(details) = Details::read(path, &mut self.owners) {
+                    self.details.insert(path.clone(), details);
+                    self.items.push(path.clone());
+                }
+            }
+        } else {
+            self.details = Self::get_details(&self.path, &mut self.owners);
+            self.items = self.get_items();
+        }
+
+        // Forget marks of entries which no longer exist
+        if !self.marked.is_empty() {
+            let items: HashSet<&PathBuf> = self.items.iter().collect();
+            self.marked.retain(|x| items.contains(x));
+        }
+
+        // Keep the cursor on the same entry, e.g. when calculated sizes reordered
+        // the listing, or inside the listing after the entry vanished
+        if let Some(index) = self.items.iter().position(|x| *x == cur_obj) {
+            self.state.select(Some(index));
+        } else if let Some(x) = self.state.selected() {
+            if x >= self.items.len() {
+                self.end();
+            }
+        } else {
+            self.begin();
+        }
     }
 
-    fn get_items(path: &Path) -> Vec<PathBuf> {
+    // Reads the metadata of every entry of the directory
+    fn get_details(path: &Path, owners: &mut OwnerCache) -> HashMap<PathBuf, Details> {
         let dir_iterator: ReadDir = match fs::read_dir(path) {
             Ok(iterator) => iterator,
             Err(_error) => {
                 // TODO -> Error message
-                return Vec::new();
+                return HashMap::new();
             }
         };
 
-        let mut dir_entries: Vec<PathBuf> = dir_iterator
+        return dir_iterator
             .filter_map(|x| x.ok())
-            .map(|x| x.path())
+            .filter_map(|x| {
+                let path: PathBuf = x.path();
+                let details: Details = Details::read(&path, owners)?;
+                Some((path, details))
+            })
             .collect();
+    }
 
-        dir_entries.sort_by(|x, y| {
-            if x.is_dir() && y.is_dir() {
-                x.cmp(&y)
-            } else if y.is_dir() {
-                Ordering::Greater
-            } else if x.is_dir() {
-                Ordering::Less
-            } else {
-                x.cmp(&y)
-            }
-        });
-
+    // Hidden entries and files not matching the filter are left out
+    fn get_items(&self) -> Vec<PathBuf> {
+        let mut dir_entries: Vec<PathBuf> = self
+            .details
+            .iter()
+            .filter(|(_, details)| self.show_hidden || !details.is_hidden())
+            .filter(|(path, details)| match &self.filter {
+                Some(filter) if !details.is_dir() => filter.matches(&get_file_name(path)),
+                _ => true,
+            })
+            .map(|(path, _)| path.clone())
+            .collect();
+        sort::sort(
+            &mut dir_entries,
+            &self.details,
+            &self.dir_sizes.get_apparent_sizes(),
+            self.sort_mode,
+        );
         return dir_entries;
     }
 }
+
+// Splits the text into spans so that the chars at the positions are highlighted
+// The positions are relative to the offset
+fn highlight(text: &str, positions: &[usize], offset: usize, style: Style) -> Spans<'static> {
+    let mut spans: Vec<Span> = Vec::new();
+    let mut chunk: String = String::new();
+    let mut is_chunk_highlighted: bool = false;
+
+    for (index, ch) in text.chars().enumerate() {
+        let is_highlighted: bool = index >= offset && positions.contains(&(index - offset));
+
+        if is_highlighted != is_chunk_highlighted && !chunk.is_empty() {
+            spans.push(match is_chunk_highlighted {
+                true => Span::styled(chunk.clone(), style),
+                false => Span::raw(chunk.clone()),
+            });
+            chunk.clear();
+        }
+
+        is_chunk_highlighted = is_highlighted;
+        chunk.push(ch);
+    }
+
+    spans.push(match is_chunk_highlighted {
+        true => Span::styled(chunk, style),
+        false => Span::raw(chunk),
+    });
+
+    return Spans::from(spans);
+}
+
+fn get_file_name(path: &Path) -> String {
+    return path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+}
@@ -1,35 +1,29 @@
 use std::path::Path;
 use tui::style::Color;
 
-const DIR_COLOR: Color = Color::Blue;
-const FILE_COLOR: Color = Color::White;
+use crate::app::config::Theme;
 
-const IMAGE_COLOR: Color = Color::Magenta;
-const AUDIO_COLOR: Color = Color::Cyan;
-const ARCHIVE_COLOR: Color = Color::Red;
-const VIDEO_COLOR: Color = Color::Magenta;
-
-const IMAGE_EXTENSIONS: &'static [&'static str] = &[
+const IMAGE_EXTENSIONS: &[&str] = &[
     "jpg", "jpeg", "jpe", "png", "bmp", "svg", "eps", "gif", "ico", "webp",
 ];
 
-const AUDIO_EXTENSIONS: &'static [&'static str] = &[
+const AUDIO_EXTENSIONS: &[&str] = &[
     "mp3", "oga", "opus", "m4a", "flac", "wav", "wma", "aac", "alac",
 ];
 
-const ARCHIVE_EXTENSIONS: &'static [&'static str] = &[
+const ARCHIVE_EXTENSIONS: &[&str] = &[
     "iso", "tar", "bz2", "gz", "lz", "lz4", "lzma", "lzo", "rz", "xz", "z", "zst", "7z", "s7z",
     "rar", "tgz", "tbz2", "tlz", "txz", "zip", "zipx", "jar",
 ];
 
-const VIDEO_EXTENSIONS: &'static [&'static str] = &[
+const VIDEO_EXTENSIONS: &[&str] = &[
     "webm", "mkv", "flv", "vob", "ogv", "ogg", "gifv", "avi", "mov", "qt", "wmv", "mp4", "m4v",
     "mp2", "mpv",
 ];
 
-pub fn get_color(path: &Path) -> Color {
+pub fn get_color(path: &Path, theme: &Theme) -> Color {
     if path.is_dir() {
-        return DIR_COLOR;
+        return theme.directory;
     }
 
     let path_extension: String = {
@@ -37,36 +31,36 @@ pub fn get_color(path: &Path) -> Color {
             if let Some(str_extension) = os_str.to_str() {
                 str_extension.to_lowercase()
             } else {
-                return FILE_COLOR;
+                return theme.file;
             }
         } else {
-            return FILE_COLOR;
+            return theme.file;
         }
     };
 
     for extension in IMAGE_EXTENSIONS {
         if path_extension == *extension {
-            return IMAGE_COLOR;
+            return theme.image;
         }
     }
 
     for extension in AUDIO_EXTENSIONS {
         if path_extension == *extension {
-            return AUDIO_COLOR;
+            return theme.audio;
         }
     }
 
     for extension in ARCHIVE_EXTENSIONS {
         if path_extension == *extension {
-            return ARCHIVE_COLOR;
+            return theme.archive;
         }
     }
 
     for extension in VIDEO_EXTENSIONS {
         if path_extension == *extension {
-            return VIDEO_COLOR;
+            return theme.video;
         }
     }
 
-    return FILE_COLOR;
+    return theme.file;
 }
@@ -0,0 +1,281 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[cfg(unix)]
+use std::time::Duration;
+
+#[cfg(unix)]
+use std::{
+    collections::HashMap,
+    ffi::CStr,
+    os::unix::fs::{FileTypeExt, MetadataExt},
+};
+
+use chrono::{DateTime, Local};
+
+use crate::app::jobs::format_size;
+
+// Optional columns of the full listing
+#[derive(Clone, Copy, PartialEq)]
+pub enum Column {
+    Size,
+    Modified,
+    Permissions,
+    Owner,
+    LinkTarget,
+}
+
+impl Column {
+    pub fn from_name(name: &str) -> Option<Column> {
+        return match name {
+            "size" => Some(Column::Size),
+            "modified" => Some(Column::Modified),
+            "permissions" => Some(Column::Permissions),
+            "owner" => Some(Column::Owner),
+            "link_target" => Some(Column::LinkTarget),
+            _ => None,
+        };
+    }
+
+    pub fn get_title(&self) -> &'static str {
+        return match self {
+            Column::Size => "Size",
+            Column::Modified => "Modified",
+            Column::Permissions => "Permissions",
+            Column::Owner => "Owner",
+            Column::LinkTarget => "Target",
+        };
+    }
+
+    // Width of the column given the width of the whole table
+    pub fn get_width(&self, table_width: u16) -> u16 {
+        return match self {
+            Column::Size => 10,
+            Column::Modified => 16,
+            Column::Permissions => 11,
+            Column::Owner => 17,
+            Column::LinkTarget => table_width / 4,
+        };
+    }
+}
+
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Size,
+    Column::Modified,
+    Column::Permissions,
+    Column::Owner,
+    Column::LinkTarget,
+];
+
+// Metadata of an entry which is read once per listing
+pub struct Details {
+    size: u64,
+    is_dir: bool,
+    is_hidden: bool,
+    modified: Option<SystemTime>,
+    changed: Option<SystemTime>,
+    permissions: String,
+    owner: String,
+    link_target: Option<PathBuf>,
+}
+
+impl Details {
+    // Symlinks are described themselves, only the directory flag follows them
+    pub fn read(path: &Path, owners: &mut OwnerCache) -> Option<Self> {
+        let metadata: fs::Metadata = fs::symlink_metadata(path).ok()?;
+        let is_symlink: bool = metadata.file_type().is_symlink();
+
+        return Some(Details {
+            size: metadata.len(),
+            is_dir: metadata.is_dir() || (is_symlink && path.is_dir()),
+            is_hidden: is_hidden(path, &metadata),
+            modified: metadata.modified().ok(),
+            changed: get_changed(&metadata),
+            permissions: get_permissions(&metadata),
+            owner: owners.get_owner(&metadata),
+            link_target: match is_symlink {
+                true => fs::read_link(path).ok(),
+                false => None,
+            },
+        });
+    }
+
+    pub fn is_dir(&self) -> bool {
+        return self.is_dir;
+    }
+
+    pub fn is_hidden(&self) -> bool {
+        return self.is_hidden;
+    }
+
+    pub fn get_size(&self) -> u64 {
+        return self.size;
+    }
+
+    pub fn get_modified(&self) -> Option<SystemTime> {
+        return self.modified;
+    }
+
+    pub fn get_changed(&self) -> Option<SystemTime> {
+        return self.changed;
+    }
+
+    pub fn get_column(&self, column: Column) -> String {
+        return match column {
+            Column::Size if self.is_dir => String::from("<DIR>"),
+            Column::Size => format_size(self.size),
+            Column::Modified => match self.modified {
+                Some(x) => DateTime::<Local>::from(x)
+                    .format("%Y-%m-%d %H:%M")
+                    .to_string(),
+                None => String::new(),
+            },
+            Column::Permissions => self.permissions.clone(),
+            Column::Owner => self.owner.clone(),
+            Column::LinkTarget => match &self.link_target {
+                Some(x) => format!["-> {}", x.display()],
+                None => String::new(),
+            },
+        };
+    }
+}
+
+// Resolves user and group ids to names, every id is looked up only once
+#[derive(Default)]
+pub struct OwnerCache {
+    #[cfg(unix)]
+    users: HashMap<u32, String>,
+    #[cfg(unix)]
+    groups: HashMap<u32, String>,
+}
+
+impl OwnerCache {
+    #[cfg(unix)]
+    fn get_owner(&mut self, metadata: &fs::Metadata) -> String {
+        let uid: u32 = metadata.uid();
+        let gid: u32 = metadata.gid();
+
+        let user: &String = self.users.entry(uid).or_insert_with(|| {
+            let passwd: *mut libc::passwd = unsafe { libc::getpwuid(uid) };
+
+            if passwd.is_null() {
+                return uid.to_string();
+            }
+
+            return unsafe { CStr::from_ptr((*passwd).pw_name) }
+                .to_string_lossy()
+                .to_string();
+        });
+        let user: String = user.clone();
+
+        let group: &String = self.groups.entry(gid).or_insert_with(|| {
+            let group: *mut libc::group = unsafe { libc::getgrgid(gid) };
+
+            if group.is_null() {
+                return gid.to_string();
+            }
+
+            return unsafe { CStr::from_ptr((*group).gr_name) }
+                .to_string_lossy()
+                .to_string();
+        });
+
+        return format!["{}:{}", user, group];
+    }
+
+    #[cfg(not(unix))]
+    fn get_owner(&mut self, _metadata: &fs::Metadata) -> String {
+        return String::new();
+    }
+}
+
+// Dotfiles and on Windows files with the hidden attribute
+fn is_hidden(path: &Path, metadata: &fs::Metadata) -> bool {
+    let is_dotfile: bool = path
+        .file_name()
+        .map(|x| x.to_string_lossy().starts_with('.'))
+        .unwrap_or(false);
+
+    #[cfg(windows)]
+    {
+        use std::os::windows::fs::MetadataExt;
+        const FILE_ATTRIBUTE_HIDDEN: u32 = 0x2;
+
+        return is_dotfile || metadata.file_attributes() & FILE_ATTRIBUTE_HIDDEN != 0;
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = metadata;
+        return is_dotfile;
+    }
+}
+
+// Time of the last status change
+#[cfg(unix)]
+fn get_changed(metadata: &fs::Metadata) -> Option<SystemTime> {
+    let duration: Duration = Duration::new(
+        u64::try_from(metadata.ctime()).ok()?,
+        u32::try_from(metadata.ctime_nsec()).ok()?,
+    );
+
+    return SystemTime::UNIX_EPOCH.checked_add(duration);
+}
+
+// Windows has no status change time, the creation time comes closest
+#[cfg(not(unix))]
+fn get_changed(metadata: &fs::Metadata) -> Option<SystemTime> {
+    return metadata.created().ok();
+}
+
+// "drwxr-xr-x" like ls
+#[cfg(unix)]
+fn get_permissions(metadata: &fs::Metadata) -> String {
+    let mode: u32 = metadata.mode();
+    let file_type: fs::FileType = metadata.file_type();
+    let type_char: char = if file_type.is_dir() {
+        'd'
+    } else if file_type.is_symlink() {
+        'l'
+    } else if file_type.is_fifo() {
+        'p'
+    } else if file_type.is_socket() {
+        's'
+    } else if file_type.is_char_device() {
+        'c'
+    } else if file_type.is_block_device() {
+        'b'
+    } else {
+        '-'
+    };
+
+    let mut permissions: String = String::from(type_char);
+
+    // (shift of the rwx bits, setuid/setgid/sticky bit, its char)
+    for (shift, special, special_char) in [(6, 0o4000, 's'), (3, 0o2000, 's'), (0, 0o1000, 't')] {
+        let bits: u32 = (mode >> shift) & 0o7;
+        let is_special: bool = mode & special != 0;
+
+        permissions.push(if bits & 0o4 != 0 { 'r' } else { '-' });
+        permissions.push(if bits & 0o2 != 0 { 'w' } else { '-' });
+        permissions.push(match (bits & 0o1 != 0, is_special) {
+            (true, true) => special_char,
+            (false, true) => special_char.to_ascii_uppercase(),
+            (true, false) => 'x',
+            (false, false) => '-',
+        });
+    }
+
+    return permissions;
+}
+
+#[cfg(not(unix))]
+fn get_permissions(metadata: &fs::Metadata) -> String {
+    return match metadata.permissions().readonly() {
+        true => String::from("read-only"),
+        false => String::from("read-write"),
+    };
+}
@@ -0,0 +1,296 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+
+use crate::app::jobs::format_size;
+
+// Entries counted between two updates of the running total
+const UPDATE_INTERVAL: u64 = 1000;
+
+// Recursive size of a directory
+#[derive(Clone, Copy, Default)]
+pub struct DirSize {
+    pub apparent: u64,
+    // Allocated blocks, hard linked files are counted once
+    pub on_disk: u64,
+    pub files: u64,
+    // Directories which could not be read
+    pub errors: u64,
+}
+
+impl DirSize {
+    pub fn describe(&self) -> String {
+        let mut text: String = format![
+            "{}, {} on disk, {} files",
+            format_size(self.apparent),
+            format_size(self.on_disk),
+            self.files
+        ];
+
+        if self.errors > 0 {
+            text = format!["{}, {} folders unreadable", text, self.errors];
+        }
+
+        return text;
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum SizeState {
+    // Total counted so far
+    Computing(DirSize),
+    Done(DirSize),
+}
+
+impl SizeState {
+    pub fn get_size(&self) -> DirSize {
+        return match self {
+            SizeState::Computing(x) | SizeState::Done(x) => *x,
+        };
+    }
+
+    // Short text for the size column, "..." marks a running calculation
+    pub fn get_column(&self) -> String {
+        return match self {
+            SizeState::Computing(x) => format!["{}...", format_size(x.apparent)],
+            SizeState::Done(x) => format_size(x.apparent),
+        };
+    }
+}
+
+// Directory sizes calculated in background threads, shared by both panels
+// Results are kept per path until they are calculated again or cleared
+#[derive(Clone, Default)]
+pub struct DirSizes {
+    cache: Arc<Mutex<HashMap<PathBuf, SizeState>>>,
+    // Set when a calculation finished
+    changed: Arc<AtomicBool>,
+    // Increased by clear, running calculations of older generations stop
+    generation: Arc<AtomicUsize>,
+}
+
+impl DirSizes {
+    // Directories which are being calculated already are left out
+    pub fn compute(&self, paths: Vec<PathBuf>) {
+        // The generation is read under the lock so that a clear in between
+        // cancels the new calculation as well
+        let (paths, started): (Vec<PathBuf>, usize) = {
+            let mut cache = self.cache.lock().unwrap();
+            let mut new_paths: Vec<PathBuf> = Vec::new();
+
+            for path in paths {
+                if !matches!(cache.get(&path), Some(SizeState::Computing(_))) {
+                    cache.insert(path.clone(), SizeState::Computing(DirSize::default()));
+                    new_paths.push(path);
+                }
+            }
+
+            (new_paths, self.generation.load(Ordering::Relaxed))
+        };
+
+        if paths.is_empty() {
+            return;
+        }
+
+        let walker: Walker = Walker {
+            cache: self.cache.clone(),
+            generation: self.generation.clone(),
+            started,
+        };
+        let changed: Arc<AtomicBool> = self.changed.clone();
+
+        thread::spawn(move || {
+            for path in paths {
+                let size: DirSize = match walker.walk(&path) {
+                    Some(x) => x,
+                    None => return,
+                };
+
+                if !walker.publish(&path, SizeState::Done(size)) {
+                    return;
+                }
+                changed.store(true, Ordering::Relaxed);
+            }
+        });
+    }
+
+    // Forgets all results and stops the running calculations
+    pub fn clear(&self) {
+        let mut cache = self.cache.lock().unwrap();
+        self.generation.fetch_add(1, Ordering::Relaxed);
+        cache.clear();
+        self.changed.store(true, Ordering::Relaxed);
+    }
+
+    pub fn get(&self, path: &Path) -> Option<SizeState> {
+        return self.cache.lock().unwrap().get(path).copied();
+    }
+
+    // Apparent sizes of all known folders, taken at once so that a sort sees
+    // the same size for an entry in every comparison
+    pub fn get_apparent_sizes(&self) -> HashMap<PathBuf, u64> {
+        return self
+            .cache
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(path, state)| (path.clone(), state.get_size().apparent))
+            .collect();
+    }
+
+    pub fn is_computing(&self) -> bool {
+        return self
+            .cache
+            .lock()
+            .unwrap()
+            .values()
+            .any(|x| matches!(x, SizeState::Computing(_)));
+    }
+
+    // Returns whether a calculation finished since the last call
+    pub fn take_changed(&self) -> bool {
+        return self.changed.swap(false, Ordering::Relaxed);
+    }
+}
+
+// Calculation of one thread, stops once the sizes were cleared
+struct Walker {
+    cache: Arc<Mutex<HashMap<PathBuf, SizeState>>>,
+    generation: Arc<AtomicUsize>,
+    started: usize,
+}
+
+impl Walker {
+    fn is_cancelled(&self) -> bool {
+        return self.generation.load(Ordering::Relaxed) != self.started;
+    }
+
+    // Returns false if the calculation was cancelled
+    fn publish(&self, path: &Path, state: SizeState) -> bool {
+        let mut cache = self.cache.lock().unwrap();
+
+        if self.is_cancelled() {
+            return false;
+        }
+
+        cache.insert(path.to_path_buf(), state);
+        return true;
+    }
+
+    // Walks the tree without following symlinks or leaving the file system of
+    // the root, e.g. into /proc, and publishes the running total
+    fn walk(&self, root: &Path) -> Option<DirSize> {
+        let mut size: DirSize = DirSize::default();
+        let mut hard_links: HashSet<(u64, u64)> = HashSet::new();
+        let mut dirs: Vec<PathBuf> = vec![root.to_path_buf()];
+        let mut until_update: u64 = UPDATE_INTERVAL;
+
+        let device: u64 = match fs::symlink_metadata(root) {
+            Ok(x) => get_device(&x),
+            Err(_) => {
+                size.errors += 1;
+                return Some(size);
+            }
+        };
+
+        while let Some(dir) = dirs.pop() {
+            if self.is_cancelled() {
+                return None;
+            }
+
+            let entries: fs::ReadDir = match fs::read_dir(&dir) {
+                Ok(x) => x,
+                Err(_) => {
+                    size.errors += 1;
+                    continue;
+                }
+            };
+
+            for entry in entries.filter_map(|x| x.ok()) {
+                let metadata: fs::Metadata = match entry.metadata() {
+                    Ok(x) => x,
+                    Err(_) => continue,
+                };
+
+                if metadata.is_dir() {
+                    if get_device(&metadata) != device {
+                        continue;
+                    }
+
+                    size.on_disk += get_allocated_size(&metadata);
+                    dirs.push(entry.path());
+                    continue;
+                }
+
+                if is_hard_link(&metadata) && !hard_links.insert(get_file_id(&metadata)) {
+                    continue;
+                }
+
+                size.apparent += metadata.len();
+                size.on_disk += get_allocated_size(&metadata);
+                size.files += 1;
+
+                until_update -= 1;
+                if until_update == 0 {
+                    until_update = UPDATE_INTERVAL;
+
+                    if !self.publish(root, SizeState::Computing(size)) {
+                        return None;
+                    }
+                }
+            }
+        }
+
+        return Some(size);
+    }
+}
+
+#[cfg(unix)]
+fn get_allocated_size(metadata: &fs::Metadata) -> u64 {
+    return metadata.blocks() * 512;
+}
+
+#[cfg(not(unix))]
+fn get_allocated_size(metadata: &fs::Metadata) -> u64 {
+    return metadata.len();
+}
+
+#[cfg(unix)]
+fn is_hard_link(metadata: &fs::Metadata) -> bool {
+    return metadata.nlink() > 1;
+}
+
+#[cfg(not(unix))]
+fn is_hard_link(_metadata: &fs::Metadata) -> bool {
+    return false;
+}
+
+#[cfg(unix)]
+fn get_file_id(metadata: &fs::Metadata) -> (u64, u64) {
+    return (metadata.dev(), metadata.ino());
+}
+
+#[cfg(not(unix))]
+fn get_file_id(_metadata: &fs::Metadata) -> (u64, u64) {
+    return (0, 0);
+}
+
+#[cfg(unix)]
+fn get_device(metadata: &fs::Metadata) -> u64 {
+    return metadata.dev();
+}
+
+#[cfg(not(unix))]
+fn get_device(_metadata: &fs::Metadata) -> u64 {
+    return 0;
+}
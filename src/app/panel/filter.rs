@@ -0,0 +1,46 @@
+use glob::Pattern;
+use regex::Regex;
+
+const REGEX_PREFIX: &str = "re:";
+
+enum Matcher {
+    Glob(Pattern),
+    Regex(Regex),
+}
+
+// Restricts the listed files to those whose name matches
+pub struct Filter {
+    text: String,
+    matcher: Matcher,
+}
+
+impl Filter {
+    // "re:<regex>" is a regular expression, everything else a glob pattern
+    pub fn new(text: &str) -> Result<Self, String> {
+        let matcher: Matcher = match text.strip_prefix(REGEX_PREFIX) {
+            Some(regex) => Matcher::Regex(
+                Regex::new(regex).map_err(|x| format!["Invalid regex {} [Error: {}]", regex, x])?,
+            ),
+            None => Matcher::Glob(
+                Pattern::new(text)
+                    .map_err(|x| format!["Invalid glob pattern {} [Error: {}]", text, x])?,
+            ),
+        };
+
+        return Ok(Filter {
+            text: text.to_string(),
+            matcher,
+        });
+    }
+
+    pub fn get_text(&self) -> &str {
+        return &self.text;
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        return match &self.matcher {
+            Matcher::Glob(pattern) => pattern.matches(name),
+            Matcher::Regex(regex) => regex.is_match(name),
+        };
+    }
+}
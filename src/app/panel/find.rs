@@ -0,0 +1,184 @@
+use std::{
+    fs,
+    path::Path,
+    time::{Duration, SystemTime},
+};
+
+use chrono::{Local, NaiveDate, TimeZone};
+
+use super::{filter::Filter, results::Matcher};
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum FileKind {
+    Any,
+    File,
+    Directory,
+    Symlink,
+}
+
+impl FileKind {
+    // Choices of the find dialog in this order
+    pub const ALL: &'static [FileKind] = &[
+        FileKind::Any,
+        FileKind::File,
+        FileKind::Directory,
+        FileKind::Symlink,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            FileKind::Any => "any",
+            FileKind::File => "file",
+            FileKind::Directory => "directory",
+            FileKind::Symlink => "symlink",
+        };
+    }
+}
+
+// Empty fields of the find dialog do not restrict the results
+pub struct FindCriteria {
+    name: Option<Filter>,
+    min_size: Option<u64>,
+    max_size: Option<u64>,
+    modified_after: Option<SystemTime>,
+    modified_before: Option<SystemTime>,
+    kind: FileKind,
+}
+
+impl FindCriteria {
+    pub fn new(
+        name: &str,
+        min_size: &str,
+        max_size: &str,
+        modified_after: &str,
+        modified_before: &str,
+        kind: FileKind,
+    ) -> Result<Self, String> {
+        return Ok(FindCriteria {
+            name: match name.is_empty() {
+                true => None,
+                false => Some(Filter::new(name)?),
+            },
+            min_size: parse_size(min_size)?,
+            max_size: parse_size(max_size)?,
+            modified_after: parse_time(modified_after)?,
+            modified_before: parse_time(modified_before)?,
+            kind,
+        });
+    }
+}
+
+impl Matcher for FindCriteria {
+    // Size limits only match files since the size of directories says nothing
+    fn check(&self, path: &Path, metadata: &fs::Metadata) -> Option<String> {
+        let file_type: fs::FileType = metadata.file_type();
+
+        let is_kind: bool = match self.kind {
+            FileKind::Any => true,
+            FileKind::File => file_type.is_file(),
+            FileKind::Directory => file_type.is_dir(),
+            FileKind::Symlink => file_type.is_symlink(),
+        };
+
+        if !is_kind {
+            return None;
+        }
+
+        if let Some(name) = &self.name {
+            let file_name: String = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+
+            if !name.matches(&file_name) {
+                return None;
+            }
+        }
+
+        if self.min_size.is_some() || self.max_size.is_some() {
+            if file_type.is_dir() {
+                return None;
+            }
+
+            let size: u64 = metadata.len();
+
+            if self.min_size.map(|x| size < x).unwrap_or(false)
+                || self.max_size.map(|x| size > x).unwrap_or(false)
+            {
+                return None;
+            }
+        }
+
+        if self.modified_after.is_some() || self.modified_before.is_some() {
+            let modified: SystemTime = match metadata.modified() {
+                Ok(x) => x,
+                Err(_) => return None,
+            };
+
+            if self.modified_after.map(|x| modified < x).unwrap_or(false)
+                || self.modified_before.map(|x| modified > x).unwrap_or(false)
+            {
+                return None;
+            }
+        }
+
+        return Some(String::new());
+    }
+}
+
+// "1024", "10K", "1.5M", "2G" with binary units
+fn parse_size(text: &str) -> Result<Option<u64>, String> {
+    let text: &str = text.trim();
+
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let (number, factor): (&str, f64) = match text.to_uppercase().chars().last() {
+        Some('K') => (&text[..text.len() - 1], 1024.0),
+        Some('M') => (&text[..text.len() - 1], 1024.0 * 1024.0),
+        Some('G') => (&text[..text.len() - 1], 1024.0 * 1024.0 * 1024.0),
+        Some('T') => (&text[..text.len() - 1], 1024.0 * 1024.0 * 1024.0 * 1024.0),
+        _ => (text, 1.0),
+    };
+
+    return match number.trim().parse::<f64>() {
+        Ok(x) if x >= 0.0 => Ok(Some((x * factor) as u64)),
+        _ => Err(format!["Invalid size {}, use e.g. 500, 10K or 1.5M", text]),
+    };
+}
+
+// A date like "2024-01-31" or an age like "30m", "12h", "7d" or "2w"
+fn parse_time(text: &str) -> Result<Option<SystemTime>, String> {
+    let text: &str = text.trim();
+
+    if text.is_empty() {
+        return Ok(None);
+    }
+
+    let error: String = format!["Invalid time {}, use e.g. 2024-01-31 or 7d", text];
+
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        let time = Local
+            .from_local_datetime(&date.and_hms_opt(0, 0, 0).unwrap())
+            .earliest()
+            .ok_or(error)?;
+        return Ok(Some(SystemTime::from(time)));
+    }
+
+    let unit_secs: u64 = match text.chars().last() {
+        Some('m') => 60,
+        Some('h') => 60 * 60,
+        Some('d') => 60 * 60 * 24,
+        Some('w') => 60 * 60 * 24 * 7,
+        _ => return Err(error),
+    };
+
+    let secs: u64 = match text[..text.len() - 1].parse::<u64>() {
+        Ok(x) => x.checked_mul(unit_secs).ok_or(error)?,
+        Err(_) => return Err(error),
+    };
+
+    return Ok(SystemTime::now().checked_sub(Duration::from_secs(secs)));
+}
@@ -0,0 +1,143 @@
+use std::{
+    fs,
+    io::{BufRead, BufReader},
+    path::Path,
+};
+
+use glob::Pattern;
+use regex::{Regex, RegexBuilder};
+
+use super::results::Matcher;
+
+// Files with a NUL byte in their beginning are considered binary
+const BINARY_CHECK_SIZE: usize = 8192;
+const MAX_PREVIEW_LEN: usize = 200;
+
+// Searches the content of the files below the root
+pub struct GrepCriteria {
+    regex: Regex,
+    // Only files matching one of them are searched, all if empty
+    include: Vec<Pattern>,
+    // Files and folders matching one of them are skipped
+    exclude: Vec<Pattern>,
+}
+
+impl GrepCriteria {
+    // The globs are separated by spaces or commas
+    pub fn new(
+        pattern: &str,
+        is_regex: bool,
+        ignore_case: bool,
+        include: &str,
+        exclude: &str,
+    ) -> Result<Self, String> {
+        if pattern.is_empty() {
+            return Err(String::from("The pattern must not be empty"));
+        }
+
+        let regex_text: String = match is_regex {
+            true => pattern.to_string(),
+            false => regex::escape(pattern),
+        };
+
+        let regex: Regex = RegexBuilder::new(&regex_text)
+            .case_insensitive(ignore_case)
+            .build()
+            .map_err(|x| format!["Invalid regex {} [Error: {}]", pattern, x])?;
+
+        return Ok(GrepCriteria {
+            regex,
+            include: parse_globs(include)?,
+            exclude: parse_globs(exclude)?,
+        });
+    }
+
+    fn is_excluded(&self, path: &Path) -> bool {
+        let name: String = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+
+        return self.exclude.iter().any(|x| x.matches(&name));
+    }
+}
+
+impl Matcher for GrepCriteria {
+    // The preview is the first matching line with its line number
+    fn check(&self, path: &Path, metadata: &fs::Metadata) -> Option<String> {
+        if !metadata.is_file() || self.is_excluded(path) {
+            return None;
+        }
+
+        if !self.include.is_empty() {
+            let name: String = path.file_name()?.to_string_lossy().to_string();
+
+            if !self.include.iter().any(|x| x.matches(&name)) {
+                return None;
+            }
+        }
+
+        let mut reader: BufReader<fs::File> =
+            BufReader::with_capacity(BINARY_CHECK_SIZE, fs::File::open(path).ok()?);
+
+        if is_binary(&mut reader)? {
+            return None;
+        }
+
+        let mut line: Vec<u8> = Vec::new();
+        let mut line_number: usize = 0;
+        let mut first_match: Option<String> = None;
+        let mut match_count: usize = 0;
+
+        loop {
+            line.clear();
+
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => line_number += 1,
+            }
+
+            let text = String::from_utf8_lossy(&line);
+
+            if !self.regex.is_match(&text) {
+                continue;
+            }
+
+            match_count += 1;
+
+            if first_match.is_none() {
+                let preview: String = text.trim().chars().take(MAX_PREVIEW_LEN).collect();
+                first_match = Some(format!["{}: {}", line_number, preview]);
+            }
+        }
+
+        let first_match: String = first_match?;
+
+        return match match_count {
+            1 => Some(first_match),
+            _ => Some(format!["{} (+{} more)", first_match, match_count - 1]),
+        };
+    }
+
+    fn skip_dir(&self, path: &Path) -> bool {
+        return self.is_excluded(path);
+    }
+}
+
+// Looks for a NUL byte in the buffered beginning without consuming it
+fn is_binary(reader: &mut BufReader<fs::File>) -> Option<bool> {
+    let head: &[u8] = reader.fill_buf().ok()?;
+    return Some(head.contains(&0));
+}
+
+fn parse_globs(text: &str) -> Result<Vec<Pattern>, String> {
+    return text
+        .split(|x: char| x == ',' || x.is_whitespace())
+        .filter(|x| !x.is_empty())
+        .map(|x| {
+            Pattern::new(x)
+                .map_err(|error| format!["Invalid glob pattern {} [Error: {}]", x, error])
+        })
+        .collect();
+}
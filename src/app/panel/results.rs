@@ -0,0 +1,161 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver, Sender, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+// Decides which entries of the walked tree are results
+pub trait Matcher: Send + 'static {
+    // Returns the preview shown next to a matching entry
+    fn check(&self, path: &Path, metadata: &fs::Metadata) -> Option<String>;
+
+    // Directories which are not descended into
+    fn skip_dir(&self, _path: &Path) -> bool {
+        return false;
+    }
+}
+
+// Listing of the entries found below the root, filled by a background thread
+pub struct Results {
+    root: PathBuf,
+    items: Vec<PathBuf>,
+    // Number of items handed out by take_unchecked
+    checked: usize,
+    previews: HashMap<PathBuf, String>,
+    receiver: Receiver<(PathBuf, String)>,
+    cancelled: Arc<AtomicBool>,
+    is_running: bool,
+}
+
+impl Results {
+    pub fn start(root: &Path, matcher: impl Matcher) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let cancelled: Arc<AtomicBool> = Arc::new(AtomicBool::new(false));
+        let thread_cancelled: Arc<AtomicBool> = cancelled.clone();
+        let thread_root: PathBuf = root.to_path_buf();
+
+        thread::spawn(move || {
+            walk(&thread_root, &matcher, &sender, &thread_cancelled);
+        });
+
+        return Results {
+            root: root.to_path_buf(),
+            items: Vec::new(),
+            checked: 0,
+            previews: HashMap::new(),
+            receiver,
+            cancelled,
+            is_running: true,
+        };
+    }
+
+    pub fn get_root(&self) -> &Path {
+        return &self.root;
+    }
+
+    pub fn is_running(&self) -> bool {
+        return self.is_running;
+    }
+
+    // Takes over the entries found since the last call
+    pub fn update(&mut self) {
+        loop {
+            match self.receiver.try_recv() {
+                Ok((path, preview)) => {
+                    if !preview.is_empty() {
+                        self.previews.insert(path.clone(), preview);
+                    }
+                    self.items.push(path);
+                }
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.is_running = false;
+                    break;
+                }
+            }
+        }
+    }
+
+    // Returns the entries which were found since the last call
+    pub fn take_unchecked(&mut self) -> &[PathBuf] {
+        let unchecked: &[PathBuf] = &self.items[self.checked..];
+        self.checked = self.items.len();
+        return unchecked;
+    }
+
+    // Hands out all entries again on the next call of take_unchecked
+    pub fn uncheck_all(&mut self) {
+        self.checked = 0;
+    }
+
+    pub fn get_preview(&self, path: &Path) -> Option<&str> {
+        return self.previews.get(path).map(|x| x.as_str());
+    }
+
+    // Shows the path relative to the root
+    pub fn get_display_name(&self, path: &Path) -> String {
+        return path
+            .strip_prefix(&self.root)
+            .unwrap_or(path)
+            .to_string_lossy()
+            .to_string();
+    }
+}
+
+impl Drop for Results {
+    fn drop(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+// Walks the tree depth first without following symlinks
+fn walk(
+    root: &Path,
+    matcher: &impl Matcher,
+    sender: &Sender<(PathBuf, String)>,
+    cancelled: &AtomicBool,
+) {
+    let mut dirs: Vec<PathBuf> = vec![root.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        let entries: fs::ReadDir = match fs::read_dir(&dir) {
+            Ok(x) => x,
+            Err(_) => continue,
+        };
+
+        let mut sub_dirs: Vec<PathBuf> = Vec::new();
+
+        for entry in entries.filter_map(|x| x.ok()) {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let path: PathBuf = entry.path();
+            let metadata: fs::Metadata = match fs::symlink_metadata(&path) {
+                Ok(x) => x,
+                Err(_) => continue,
+            };
+
+            if let Some(preview) = matcher.check(&path, &metadata) {
+                if sender.send((path.clone(), preview)).is_err() {
+                    return;
+                }
+            }
+
+            if metadata.is_dir() && !matcher.skip_dir(&path) {
+                sub_dirs.push(path);
+            }
+        }
+
+        // Visit the sub directories in name order
+        sub_dirs.sort();
+        sub_dirs.reverse();
+        dirs.extend(sub_dirs);
+    }
+}
@@ -0,0 +1,210 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use regex::Regex;
+use tui::{style::Style, text::Span};
+
+use crate::app::line_edit::LineEdit;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SearchMode {
+    // Case-insensitive
+    Substring,
+    // Case-insensitive
+    Prefix,
+    // Chars in order with gaps allowed, better matches are visited first
+    Fuzzy,
+    Regex,
+}
+
+impl SearchMode {
+    pub fn next(&self) -> SearchMode {
+        return match self {
+            SearchMode::Substring => SearchMode::Prefix,
+            SearchMode::Prefix => SearchMode::Fuzzy,
+            SearchMode::Fuzzy => SearchMode::Regex,
+            SearchMode::Regex => SearchMode::Substring,
+        };
+    }
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            SearchMode::Substring => "substring",
+            SearchMode::Prefix => "prefix",
+            SearchMode::Fuzzy => "fuzzy",
+            SearchMode::Regex => "regex",
+        };
+    }
+}
+
+pub struct Match {
+    pub score: i64,
+    // Char indices of the matching chars in the name
+    pub positions: Vec<usize>,
+}
+
+// Search string typed into the panel, shown with its cursor in the infos
+pub struct Search {
+    mode: SearchMode,
+    input: LineEdit,
+    // Compiled in regex mode, None while the text is no valid regex
+    regex: Option<Regex>,
+}
+
+impl Search {
+    pub fn new(mode: SearchMode) -> Self {
+        return Search {
+            mode,
+            input: LineEdit::new(""),
+            regex: None,
+        };
+    }
+
+    pub fn get_mode(&self) -> SearchMode {
+        return self.mode;
+    }
+
+    pub fn get_text(&self) -> &str {
+        return self.input.get_text();
+    }
+
+    pub fn get_spans(&self, style: Style) -> Vec<Span<'static>> {
+        return self.input.get_spans(style);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.get_text().is_empty();
+    }
+
+    pub fn next_mode(&mut self) {
+        self.mode = self.mode.next();
+        self.update_regex();
+    }
+
+    pub fn push_char(&mut self, ch: char) {
+        self.input
+            .handle_key(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        self.update_regex();
+    }
+
+    pub fn pop_char(&mut self) {
+        self.input
+            .handle_key(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+        self.update_regex();
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.input.paste(text);
+        self.update_regex();
+    }
+
+    pub fn clear(&mut self) {
+        self.input.set_text("");
+        self.regex = None;
+    }
+
+    fn update_regex(&mut self) {
+        self.regex = match self.mode {
+            SearchMode::Regex => Regex::new(self.get_text()).ok(),
+            _ => None,
+        };
+    }
+
+    pub fn find(&self, name: &str) -> Option<Match> {
+        let text: &str = self.get_text();
+
+        if text.is_empty() {
+            return None;
+        }
+
+        return match self.mode {
+            SearchMode::Substring => find_substring(text, name, false),
+            SearchMode::Prefix => find_substring(text, name, true),
+            SearchMode::Fuzzy => find_fuzzy(text, name),
+            SearchMode::Regex => {
+                let found = self.regex.as_ref()?.find(name)?;
+                let start: usize = name[..found.start()].chars().count();
+                let len: usize = found.as_str().chars().count();
+
+                Some(Match {
+                    score: 0,
+                    positions: (start..start + len).collect(),
+                })
+            }
+        };
+    }
+}
+
+fn fold(ch: char) -> char {
+    return ch.to_lowercase().next().unwrap_or(ch);
+}
+
+fn find_substring(text: &str, name: &str, prefix_only: bool) -> Option<Match> {
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let name: Vec<char> = name.chars().map(fold).collect();
+
+    if text.len() > name.len() {
+        return None;
+    }
+
+    let last_start: usize = match prefix_only {
+        true => 0,
+        false => name.len() - text.len(),
+    };
+
+    for start in 0..=last_start {
+        if name[start..start + text.len()] == text[..] {
+            return Some(Match {
+                score: 0,
+                positions: (start..start + text.len()).collect(),
+            });
+        }
+    }
+
+    return None;
+}
+
+// Takes every char of the text at its first possible position
+// Consecutive chars and chars at the start of words score higher, gaps and long
+// names lower
+fn find_fuzzy(text: &str, name: &str) -> Option<Match> {
+    let text: Vec<char> = text.chars().map(fold).collect();
+    let name: Vec<char> = name.chars().collect();
+
+    let mut positions: Vec<usize> = Vec::new();
+    let mut score: i64 = 0;
+
+    for (index, ch) in name.iter().enumerate() {
+        if positions.len() == text.len() {
+            break;
+        }
+
+        if fold(*ch) != text[positions.len()] {
+            continue;
+        }
+
+        score += 1;
+
+        if index > 0 && positions.last() == Some(&(index - 1)) {
+            score += 5;
+        }
+
+        let is_word_start: bool = index == 0
+            || !name[index - 1].is_alphanumeric()
+            || (name[index - 1].is_lowercase() && ch.is_uppercase());
+
+        if is_word_start {
+            score += 3;
+        }
+
+        positions.push(index);
+    }
+
+    if positions.len() < text.len() {
+        return None;
+    }
+
+    let span: usize = positions[positions.len() - 1] - positions[0] + 1;
+    score -= (span - positions.len()) as i64;
+    score -= (name.len() / 10) as i64;
+
+    return Some(Match { score, positions });
+}
@@ -0,0 +1,233 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::details::Details;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SortKey {
+    Name,
+    // Numbers inside names are compared by value, e.g. "file2" < "file10"
+    Natural,
+    Extension,
+    Size,
+    Modified,
+    Changed,
+}
+
+impl SortKey {
+    pub fn next(&self) -> SortKey {
+        return match self {
+            SortKey::Name => SortKey::Natural,
+            SortKey::Natural => SortKey::Extension,
+            SortKey::Extension => SortKey::Size,
+            SortKey::Size => SortKey::Modified,
+            SortKey::Modified => SortKey::Changed,
+            SortKey::Changed => SortKey::Name,
+        };
+    }
+
+    fn name(&self) -> &'static str {
+        return match self {
+            SortKey::Name => "name",
+            SortKey::Natural => "natural",
+            SortKey::Extension => "extension",
+            SortKey::Size => "size",
+            SortKey::Modified => "mtime",
+            SortKey::Changed => "ctime",
+        };
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct SortMode {
+    pub key: SortKey,
+    pub descending: bool,
+    pub ignore_case: bool,
+    pub dirs_first: bool,
+}
+
+impl Default for SortMode {
+    fn default() -> Self {
+        return SortMode {
+            key: SortKey::Name,
+            descending: false,
+            ignore_case: false,
+            dirs_first: true,
+        };
+    }
+}
+
+impl SortMode {
+    // Short description for the panel title, e.g. "name asc, dirs first"
+    pub fn describe(&self) -> String {
+        let mut text: String = format![
+            "{} {}",
+            self.key.name(),
+            match self.descending {
+                true => "desc",
+                false => "asc",
+            }
+        ];
+
+        if self.ignore_case {
+            text.push_str(", ignore case");
+        }
+
+        if self.dirs_first {
+            text.push_str(", dirs first");
+        }
+
+        return text;
+    }
+}
+
+// Sorts the entries of a directory by their cached metadata
+// Entries without metadata are treated like empty files, folders count with
+// their calculated size taken from a snapshot of the running calculations
+pub fn sort(
+    items: &mut [PathBuf],
+    details: &HashMap<PathBuf, Details>,
+    dir_sizes: &HashMap<PathBuf, u64>,
+    mode: SortMode,
+) {
+    let is_dir = |x: &PathBuf| details.get(x).map(|x| x.is_dir()).unwrap_or(false);
+
+    items.sort_by(|x, y| {
+        // Directories stay on top regardless of the order
+        if mode.dirs_first {
+            let ordering: Ordering = is_dir(y).cmp(&is_dir(x));
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        let ordering: Ordering = compare(x, y, details, dir_sizes, mode)
+            .then_with(|| compare_names(&get_name(x), &get_name(y), mode.ignore_case))
+            .then_with(|| x.cmp(y));
+
+        return match mode.descending {
+            true => ordering.reverse(),
+            false => ordering,
+        };
+    });
+}
+
+fn compare(
+    x: &Path,
+    y: &Path,
+    details: &HashMap<PathBuf, Details>,
+    dir_sizes: &HashMap<PathBuf, u64>,
+    mode: SortMode,
+) -> Ordering {
+    let x_details: Option<&Details> = details.get(x);
+    let y_details: Option<&Details> = details.get(y);
+
+    return match mode.key {
+        SortKey::Name => Ordering::Equal,
+        SortKey::Natural => compare_natural(&get_name(x), &get_name(y), mode.ignore_case),
+        SortKey::Extension => compare_names(&get_extension(x), &get_extension(y), mode.ignore_case),
+        SortKey::Size => get_size(x, x_details, dir_sizes).cmp(&get_size(y, y_details, dir_sizes)),
+        SortKey::Modified => x_details
+            .and_then(|x| x.get_modified())
+            .cmp(&y_details.and_then(|x| x.get_modified())),
+        SortKey::Changed => x_details
+            .and_then(|x| x.get_changed())
+            .cmp(&y_details.and_then(|x| x.get_changed())),
+    };
+}
+
+fn get_size(
+    path: &Path,
+    details: Option<&Details>,
+    dir_sizes: &HashMap<PathBuf, u64>,
+) -> Option<u64> {
+    if let (Some(true), Some(size)) = (details.map(|x| x.is_dir()), dir_sizes.get(path)) {
+        return Some(*size);
+    }
+
+    return details.map(|x| x.get_size());
+}
+
+fn compare_names(x: &str, y: &str, ignore_case: bool) -> Ordering {
+    if ignore_case {
+        return x.to_lowercase().cmp(&y.to_lowercase());
+    }
+
+    return x.cmp(y);
+}
+
+// Compares runs of digits by their value and everything else char by char
+fn compare_natural(x: &str, y: &str, ignore_case: bool) -> Ordering {
+    let x_chunks: Vec<&str> = split_digits(x);
+    let y_chunks: Vec<&str> = split_digits(y);
+
+    for (x_chunk, y_chunk) in x_chunks.iter().zip(y_chunks.iter()) {
+        let x_is_number: bool = x_chunk.starts_with(|c: char| c.is_ascii_digit());
+        let y_is_number: bool = y_chunk.starts_with(|c: char| c.is_ascii_digit());
+
+        let ordering: Ordering = match (x_is_number, y_is_number) {
+            (true, true) => {
+                let x_number: &str = x_chunk.trim_start_matches('0');
+                let y_number: &str = y_chunk.trim_start_matches('0');
+
+                // Longer numbers are bigger, equal lengths compare like text
+                x_number
+                    .len()
+                    .cmp(&y_number.len())
+                    .then_with(|| x_number.cmp(y_number))
+                    .then_with(|| x_chunk.len().cmp(&y_chunk.len()))
+            }
+            _ => compare_names(x_chunk, y_chunk, ignore_case),
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    return x_chunks.len().cmp(&y_chunks.len());
+}
+
+// "file10.txt" -> ["file", "10", ".txt"]
+fn split_digits(text: &str) -> Vec<&str> {
+    let mut chunks: Vec<&str> = Vec::new();
+    let mut start: usize = 0;
+    let mut was_digit: Option<bool> = None;
+
+    for (index, ch) in text.char_indices() {
+        let is_digit: bool = ch.is_ascii_digit();
+
+        if was_digit.is_some() && was_digit != Some(is_digit) {
+            chunks.push(&text[start..index]);
+            start = index;
+        }
+
+        was_digit = Some(is_digit);
+    }
+
+    if start < text.len() {
+        chunks.push(&text[start..]);
+    }
+
+    return chunks;
+}
+
+fn get_name(path: &Path) -> String {
+    return path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+}
+
+fn get_extension(path: &Path) -> String {
+    return path
+        .extension()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+}
@@ -0,0 +1,98 @@
+use chrono::{Local, TimeZone};
+use trash::{Error, TrashItem};
+
+// The trash can only be browsed on Windows and freedesktop compliant systems
+#[cfg(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+))]
+mod platform {
+    use trash::{os_limited, Error, TrashItem};
+
+    pub fn list() -> Result<Vec<TrashItem>, Error> {
+        return os_limited::list();
+    }
+
+    pub fn restore(items: Vec<TrashItem>) -> Result<(), Error> {
+        return os_limited::restore_all(items);
+    }
+
+    pub fn purge(items: Vec<TrashItem>) -> Result<(), Error> {
+        return os_limited::purge_all(items);
+    }
+}
+
+#[cfg(not(any(
+    target_os = "windows",
+    all(
+        unix,
+        not(target_os = "macos"),
+        not(target_os = "ios"),
+        not(target_os = "android")
+    )
+)))]
+mod platform {
+    use trash::{Error, TrashItem};
+
+    fn unsupported() -> Error {
+        return Error::Unknown {
+            description: String::from("The trash cannot be browsed on this platform"),
+        };
+    }
+
+    pub fn list() -> Result<Vec<TrashItem>, Error> {
+        return Err(unsupported());
+    }
+
+    pub fn restore(_items: Vec<TrashItem>) -> Result<(), Error> {
+        return Err(unsupported());
+    }
+
+    pub fn purge(_items: Vec<TrashItem>) -> Result<(), Error> {
+        return Err(unsupported());
+    }
+}
+
+// Returns the trashed items, the most recently deleted first
+pub fn list() -> Result<Vec<TrashItem>, Error> {
+    let mut items: Vec<TrashItem> = platform::list()?;
+    items.sort_by_key(|x| std::cmp::Reverse(x.time_deleted));
+    return Ok(items);
+}
+
+pub fn restore(items: Vec<TrashItem>) -> Result<(), Error> {
+    return platform::restore(items);
+}
+
+pub fn purge(items: Vec<TrashItem>) -> Result<(), Error> {
+    return platform::purge(items);
+}
+
+// TrashItem does not implement Clone
+pub fn clone_item(item: &TrashItem) -> TrashItem {
+    return TrashItem {
+        id: item.id.clone(),
+        name: item.name.clone(),
+        original_parent: item.original_parent.clone(),
+        time_deleted: item.time_deleted,
+    };
+}
+
+pub fn format_item(item: &TrashItem) -> String {
+    let time_deleted: String = match Local.timestamp_opt(item.time_deleted, 0).single() {
+        Some(x) => x.format("%Y-%m-%d %H:%M").to_string(),
+        None => String::from("unknown"),
+    };
+
+    return format![
+        "{}  [{}, deleted {}]",
+        item.name,
+        item.original_parent.display(),
+        time_deleted
+    ];
+}
@@ -1,16 +1,76 @@
+use std::path::PathBuf;
+
+use trash::TrashItem;
+
 use tui::{
     backend::Backend,
     layout::{Alignment, Constraint, Direction, Layout, Rect},
-    style::{Color, Style},
-    text::Text,
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
 
+const SELECTED_BUTTON_COLOR: Color = Color::LightGreen;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Button {
+    Ok,
+    Yes,
+    No,
+    Cancel,
+    Overwrite,
+    Skip,
+    Rename,
+    OverwriteIfNewer,
+}
+
+impl Button {
+    fn label(&self) -> &'static str {
+        return match self {
+            Button::Ok => "Ok",
+            Button::Yes => "Yes",
+            Button::No => "No",
+            Button::Cancel => "Cancel",
+            Button::Overwrite => "Overwrite",
+            Button::Skip => "Skip",
+            Button::Rename => "Rename",
+            Button::OverwriteIfNewer => "If newer",
+        };
+    }
+
+    // The first letter of the label selects the button
+    fn shortcut(&self) -> char {
+        return match self {
+            Button::OverwriteIfNewer => 'n',
+            _ => self.label().chars().next().unwrap().to_ascii_lowercase(),
+        };
+    }
+}
+
+// Executed by the app once a button of the dialog got pressed
+pub enum PopupAction {
+    Delete(Vec<PathBuf>),
+    PermanentDelete(Vec<PathBuf>),
+    ResolveConflict,
+    Restore(Vec<TrashItem>),
+    RestoreTo(Vec<TrashItem>, PathBuf),
+    Purge(Vec<TrashItem>),
+    Rename(Vec<(PathBuf, PathBuf)>),
+    Quit,
+}
+
 pub struct Popup {
     title: String,
     text: String,
     style: Option<Style>,
+    buttons: Vec<Button>,
+    selected: usize,
+    option: Option<(String, bool)>,
+    action: Option<PopupAction>,
+    alignment: Alignment,
+    // Lines scrolled out at the top for texts longer than the popup
+    scroll: u16,
 }
 
 impl Popup {
@@ -18,10 +78,102 @@ impl Popup {
         return Popup {
             title: title.to_string(),
             text: text.to_string(),
-            style: style,
+            style,
+            buttons: vec![Button::Ok],
+            selected: 0,
+            option: None,
+            action: None,
+            alignment: Alignment::Center,
+            scroll: 0,
         };
     }
 
+    // Creates a dialog whose action is executed depending on the pressed button
+    // The first button is selected by default
+    pub fn dialog(
+        title: impl ToString,
+        text: impl ToString,
+        buttons: &[Button],
+        action: PopupAction,
+    ) -> Self {
+        return Popup {
+            title: title.to_string(),
+            text: text.to_string(),
+            style: None,
+            buttons: buttons.to_vec(),
+            selected: 0,
+            option: None,
+            action: Some(action),
+            alignment: Alignment::Center,
+            scroll: 0,
+        };
+    }
+
+    // Adds a checkbox which is toggled with Space
+    pub fn with_option(mut self, label: impl ToString) -> Self {
+        self.option = Some((label.to_string(), false));
+        return self;
+    }
+
+    pub fn with_alignment(mut self, alignment: Alignment) -> Self {
+        self.alignment = alignment;
+        return self;
+    }
+
+    pub fn take_action(&mut self) -> Option<PopupAction> {
+        return self.action.take();
+    }
+
+    pub fn is_option_checked(&self) -> bool {
+        return matches!(self.option, Some((_, true)));
+    }
+
+    pub fn toggle_option(&mut self) {
+        if let Some((_, checked)) = self.option.as_mut() {
+            *checked = !*checked;
+        }
+    }
+
+    pub fn scroll_up(&mut self, lines: u16) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: u16) {
+        let max_scroll: u16 = self.text.lines().count().saturating_sub(1) as u16;
+        self.scroll = (self.scroll + lines).min(max_scroll);
+    }
+
+    pub fn next_button(&mut self) {
+        self.selected = (self.selected + 1) % self.buttons.len();
+    }
+
+    pub fn previous_button(&mut self) {
+        self.selected = (self.selected + self.buttons.len() - 1) % self.buttons.len();
+    }
+
+    pub fn get_selected_button(&self) -> Button {
+        return self.buttons[self.selected];
+    }
+
+    pub fn get_button_by_shortcut(&self, ch: char) -> Option<Button> {
+        return self
+            .buttons
+            .iter()
+            .find(|x| x.shortcut() == ch.to_ascii_lowercase())
+            .copied();
+    }
+
+    // Button which is pressed by Esc
+    pub fn get_escape_button(&self) -> Button {
+        for button in [Button::Cancel, Button::No, Button::Ok] {
+            if self.buttons.contains(&button) {
+                return button;
+            }
+        }
+
+        return self.buttons[self.buttons.len() - 1];
+    }
+
     pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
         let popup_layout: Vec<Rect> = Layout::default()
             .direction(Direction::Horizontal)
@@ -29,17 +181,41 @@ impl Popup {
             .margin(10)
             .split(f.size());
 
-        let text: Text;
+        let mut text: Text;
 
-        if self.style.is_some() {
-            text = Text::styled(
-                format!["{}\n\n[Press Enter or Esc]", self.text],
-                self.style.clone().unwrap(),
-            );
+        if let Some(style) = self.style {
+            text = Text::styled(format!["{}\n", self.text], style);
         } else {
-            text = Text::from(format!["{}\n\n[Press Enter or Esc]", self.text]);
+            text = Text::from(format!["{}\n", self.text]);
+        }
+
+        if let Some((label, checked)) = &self.option {
+            let checkbox: &str = match checked {
+                true => "[x]",
+                false => "[ ]",
+            };
+
+            text.extend(Text::from(format!["{} {} [Space]\n", checkbox, label]));
         }
 
+        let mut buttons: Vec<Span> = Vec::new();
+
+        for (index, button) in self.buttons.iter().enumerate() {
+            let mut style: Style = Style::default();
+
+            if index == self.selected {
+                style = style
+                    .fg(Color::Black)
+                    .bg(SELECTED_BUTTON_COLOR)
+                    .add_modifier(Modifier::BOLD);
+            }
+
+            buttons.push(Span::styled(format!["[ {} ]", button.label()], style));
+            buttons.push(Span::raw("  "));
+        }
+
+        text.extend(Text::from(Spans::from(buttons)));
+
         let popup_msg: Paragraph = Paragraph::new(text)
             .block(
                 Block::default()
@@ -47,8 +223,9 @@ impl Popup {
                     .borders(Borders::ALL),
             )
             .style(Style::default().fg(Color::White).bg(Color::Black))
-            .alignment(Alignment::Center)
-            .wrap(Wrap { trim: true });
+            .alignment(self.alignment)
+            .wrap(Wrap { trim: true })
+            .scroll((self.scroll, 0));
 
         f.render_widget(Clear, popup_layout[0]);
         f.render_widget(popup_msg, popup_layout[0]);
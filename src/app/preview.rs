@@ -0,0 +1,271 @@
+use tui::{
+    backend::Backend,
+    layout::Rect,
+    style::{Color, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Paragraph},
+    Frame,
+};
+
+use std::{
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+
+use super::jobs::format_size;
+
+mod highlight;
+use highlight::Language;
+
+// Only the beginning of large files is read
+const MAX_TEXT_SIZE: u64 = 256 * 1024;
+const MAX_TEXT_LINES: usize = 1000;
+const MAX_HEX_SIZE: u64 = 4096;
+const HEX_BYTES_PER_LINE: usize = 16;
+// Files with a NUL byte in their beginning are shown as hex dump
+const BINARY_CHECK_SIZE: usize = 8192;
+const TAB_WIDTH: usize = 4;
+
+const GUTTER_COLOR: Color = Color::DarkGray;
+
+// Quick-view of the entry under the cursor, shown in place of the other panel
+// The content is read again only when the path or its modification time changes
+pub struct Preview {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    kind: &'static str,
+    lines: Vec<Spans<'static>>,
+}
+
+impl Preview {
+    pub fn new() -> Self {
+        return Preview {
+            path: PathBuf::new(),
+            modified: None,
+            kind: "",
+            lines: Vec::new(),
+        };
+    }
+
+    pub fn update(&mut self, path: &Path) {
+        let metadata: Option<fs::Metadata> = fs::metadata(path).ok();
+        let modified: Option<SystemTime> = metadata.as_ref().and_then(|x| x.modified().ok());
+
+        if path == self.path && modified.is_some() && modified == self.modified {
+            return;
+        }
+
+        self.path = path.to_path_buf();
+        self.modified = modified;
+
+        let metadata: fs::Metadata = match metadata {
+            Some(x) => x,
+            None => {
+                self.kind = "";
+                self.lines = match path.as_os_str().is_empty() {
+                    true => Vec::new(),
+                    false => vec![Spans::from("Cannot be read")],
+                };
+                return;
+            }
+        };
+
+        let result: Result<(), String> = if metadata.is_dir() {
+            self.load_dir(path)
+        } else if metadata.is_file() {
+            self.load_file(path)
+        } else {
+            self.load_special(&metadata);
+            Ok(())
+        };
+
+        if let Err(error) = result {
+            self.kind = "";
+            self.lines = vec![Spans::from(format!["Cannot be read [Error: {}]", error])];
+        }
+    }
+
+    fn load_dir(&mut self, path: &Path) -> Result<(), String> {
+        let mut dir_count: usize = 0;
+        let mut file_count: usize = 0;
+        let mut other_count: usize = 0;
+        let mut total_size: u64 = 0;
+
+        for entry in fs::read_dir(path).map_err(|x| x.to_string())? {
+            let metadata: fs::Metadata = match entry.and_then(|x| x.metadata()) {
+                Ok(x) => x,
+                Err(_) => {
+                    other_count += 1;
+                    continue;
+                }
+            };
+
+            if metadata.is_dir() {
+                dir_count += 1;
+            } else if metadata.is_file() {
+                file_count += 1;
+                total_size += metadata.len();
+            } else {
+                other_count += 1;
+            }
+        }
+
+        self.kind = "folder";
+        self.lines = vec![
+            Spans::from(format!["Entries: {}", dir_count + file_count + other_count]),
+            Spans::from(format!["Folders: {}", dir_count]),
+            Spans::from(format!["Files: {}", file_count]),
+            Spans::from(format!["Other: {}", other_count]),
+            Spans::from(format![
+                "Size of the files directly inside: {}",
+                format_size(total_size)
+            ]),
+        ];
+
+        return Ok(());
+    }
+
+    // Reading fifos and devices may block or never end so only the type is shown
+    fn load_special(&mut self, metadata: &fs::Metadata) {
+        self.kind = get_special_kind(metadata);
+        self.lines = vec![Spans::from(format!["No preview for a {}", self.kind])];
+    }
+
+    fn load_file(&mut self, path: &Path) -> Result<(), String> {
+        let mut data: Vec<u8> = Vec::new();
+
+        fs::File::open(path)
+            .and_then(|x| x.take(MAX_TEXT_SIZE).read_to_end(&mut data))
+            .map_err(|x| x.to_string())?;
+
+        let head: &[u8] = &data[..data.len().min(BINARY_CHECK_SIZE)];
+
+        if head.contains(&0) {
+            data.truncate(MAX_HEX_SIZE as usize);
+            self.kind = "binary";
+            self.lines = get_hex_lines(&data);
+        } else {
+            self.kind = "text";
+            self.lines = get_text_lines(
+                &String::from_utf8_lossy(&data),
+                highlight::get_language(path),
+            );
+        }
+
+        return Ok(());
+    }
+
+    pub fn render<B: Backend>(&self, area: Rect, f: &mut Frame<B>, color: Color) {
+        let title: String = match self.kind.is_empty() {
+            true => format!["Preview: {}", self.path.display()],
+            false => format!["Preview: {} [{}]", self.path.display(), self.kind],
+        };
+
+        let paragraph: Paragraph = Paragraph::new(Text::from(self.lines.clone())).block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(color)),
+        );
+
+        f.render_widget(paragraph, area);
+    }
+}
+
+// Numbered lines, highlighted if the language is known by its extension
+fn get_text_lines(text: &str, language: Option<&Language>) -> Vec<Spans<'static>> {
+    let lines: Vec<&str> = text.lines().take(MAX_TEXT_LINES).collect();
+    let number_width: usize = lines.len().to_string().len();
+    let mut in_block_comment: bool = false;
+
+    return lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| {
+            // Control chars would mess up the terminal
+            let line: String = line
+                .replace('\t', &" ".repeat(TAB_WIDTH))
+                .chars()
+                .map(|x| match x.is_control() {
+                    true => '.',
+                    false => x,
+                })
+                .collect();
+
+            let mut spans: Vec<Span<'static>> = vec![Span::styled(
+                format!["{:>width$} ", index + 1, width = number_width],
+                Style::default().fg(GUTTER_COLOR),
+            )];
+
+            match language {
+                Some(x) => spans.extend(highlight::highlight_line(&line, x, &mut in_block_comment)),
+                None => spans.push(Span::raw(line)),
+            }
+
+            Spans::from(spans)
+        })
+        .collect();
+}
+
+#[cfg(unix)]
+fn get_special_kind(metadata: &fs::Metadata) -> &'static str {
+    let file_type: fs::FileType = metadata.file_type();
+
+    if file_type.is_fifo() {
+        return "fifo";
+    } else if file_type.is_socket() {
+        return "socket";
+    } else if file_type.is_block_device() {
+        return "block device";
+    } else if file_type.is_char_device() {
+        return "character device";
+    }
+
+    return "special file";
+}
+
+#[cfg(not(unix))]
+fn get_special_kind(_metadata: &fs::Metadata) -> &'static str {
+    return "special file";
+}
+
+// Offset, bytes in hex and the printable ASCII chars
+fn get_hex_lines(data: &[u8]) -> Vec<Spans<'static>> {
+    return data
+        .chunks(HEX_BYTES_PER_LINE)
+        .enumerate()
+        .map(|(index, chunk)| {
+            let hex: String = chunk
+                .iter()
+                .map(|x| format!["{:02x}", x])
+                .collect::<Vec<String>>()
+                .join(" ");
+
+            let ascii: String = chunk
+                .iter()
+                .map(|x| match x.is_ascii_graphic() || *x == b' ' {
+                    true => *x as char,
+                    false => '.',
+                })
+                .collect();
+
+            Spans::from(vec![
+                Span::styled(
+                    format!["{:08x} ", index * HEX_BYTES_PER_LINE],
+                    Style::default().fg(GUTTER_COLOR),
+                ),
+                Span::raw(format![
+                    "{:width$} {}",
+                    hex,
+                    ascii,
+                    width = HEX_BYTES_PER_LINE * 3 - 1
+                ]),
+            ])
+        })
+        .collect();
+}
@@ -0,0 +1,236 @@
+use std::path::Path;
+
+use tui::{
+    style::{Color, Style},
+    text::Span,
+};
+
+const KEYWORD_COLOR: Color = Color::LightMagenta;
+const STRING_COLOR: Color = Color::LightGreen;
+const COMMENT_COLOR: Color = Color::DarkGray;
+const NUMBER_COLOR: Color = Color::LightCyan;
+
+// Minimal description of a language, enough to color keywords, strings, numbers
+// and comments
+pub struct Language {
+    extensions: &'static [&'static str],
+    line_comments: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+    keywords: &'static [&'static str],
+}
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        extensions: &["rs"],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"'],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+            "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod",
+            "move", "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super",
+            "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+    },
+    Language {
+        extensions: &[
+            "c", "h", "cc", "cpp", "cxx", "hpp", "java", "js", "jsx", "ts", "tsx", "go", "cs",
+            "kt", "swift", "scala", "dart",
+        ],
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+        keywords: &[
+            "auto",
+            "break",
+            "case",
+            "catch",
+            "class",
+            "const",
+            "continue",
+            "default",
+            "do",
+            "else",
+            "enum",
+            "export",
+            "extends",
+            "false",
+            "final",
+            "finally",
+            "for",
+            "func",
+            "function",
+            "go",
+            "if",
+            "import",
+            "interface",
+            "let",
+            "namespace",
+            "new",
+            "null",
+            "package",
+            "private",
+            "protected",
+            "public",
+            "return",
+            "static",
+            "struct",
+            "switch",
+            "this",
+            "throw",
+            "true",
+            "try",
+            "type",
+            "typedef",
+            "var",
+            "void",
+            "while",
+        ],
+    },
+    Language {
+        extensions: &["py", "pyw"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del",
+            "elif", "else", "except", "False", "finally", "for", "from", "global", "if", "import",
+            "in", "is", "lambda", "None", "nonlocal", "not", "or", "pass", "raise", "return",
+            "True", "try", "while", "with", "yield",
+        ],
+    },
+    Language {
+        extensions: &["sh", "bash", "zsh", "fish"],
+        line_comments: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+            "in", "local", "return", "then", "until", "while",
+        ],
+    },
+    Language {
+        extensions: &["toml", "ini", "cfg", "conf", "yaml", "yml"],
+        line_comments: &["#", ";"],
+        block_comment: None,
+        quotes: &['"', '\''],
+        keywords: &["true", "false", "yes", "no", "null"],
+    },
+    Language {
+        extensions: &["json"],
+        line_comments: &[],
+        block_comment: None,
+        quotes: &['"'],
+        keywords: &["true", "false", "null"],
+    },
+];
+
+pub fn get_language(path: &Path) -> Option<&'static Language> {
+    let extension: String = path.extension()?.to_string_lossy().to_lowercase();
+
+    return LANGUAGES
+        .iter()
+        .find(|x| x.extensions.contains(&extension.as_str()));
+}
+
+// Splits the line into colored spans
+// Block comments may span several lines so their state is carried over
+pub fn highlight_line(
+    line: &str,
+    language: &Language,
+    in_block_comment: &mut bool,
+) -> Vec<Span<'static>> {
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut plain: String = String::new();
+    // Byte offset into the line
+    let mut index: usize = 0;
+
+    let push = |spans: &mut Vec<Span<'static>>, plain: &mut String, text: &str, color: Color| {
+        if !plain.is_empty() {
+            spans.push(Span::raw(std::mem::take(plain)));
+        }
+        spans.push(Span::styled(text.to_string(), Style::default().fg(color)));
+    };
+
+    while index < line.len() {
+        let rest: &str = &line[index..];
+
+        if *in_block_comment {
+            let (_, end) = language.block_comment.unwrap();
+            let len: usize = match rest.find(end) {
+                Some(x) => {
+                    *in_block_comment = false;
+                    x + end.len()
+                }
+                None => rest.len(),
+            };
+
+            push(&mut spans, &mut plain, &rest[..len], COMMENT_COLOR);
+            index += len;
+            continue;
+        }
+
+        if language.line_comments.iter().any(|x| rest.starts_with(x)) {
+            push(&mut spans, &mut plain, rest, COMMENT_COLOR);
+            break;
+        }
+
+        if let Some((start, _)) = language.block_comment {
+            if rest.starts_with(start) {
+                *in_block_comment = true;
+                push(&mut spans, &mut plain, start, COMMENT_COLOR);
+                index += start.len();
+                continue;
+            }
+        }
+
+        let ch: char = rest.chars().next().unwrap();
+
+        if language.quotes.contains(&ch) {
+            // Up to the closing quote, escaped quotes do not count
+            let mut is_escaped: bool = false;
+            let len: usize = rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, x)| {
+                    let is_end: bool = *x == ch && !is_escaped;
+                    is_escaped = *x == '\\' && !is_escaped;
+                    is_end
+                })
+                .map(|(x, _)| x + ch.len_utf8())
+                .unwrap_or(rest.len());
+
+            push(&mut spans, &mut plain, &rest[..len], STRING_COLOR);
+            index += len;
+            continue;
+        }
+
+        if ch.is_alphanumeric() || ch == '_' {
+            let len: usize = rest
+                .find(|x: char| !x.is_alphanumeric() && x != '_')
+                .unwrap_or(rest.len());
+            let word: &str = &rest[..len];
+
+            if ch.is_ascii_digit() {
+                push(&mut spans, &mut plain, word, NUMBER_COLOR);
+            } else if language.keywords.contains(&word) {
+                push(&mut spans, &mut plain, word, KEYWORD_COLOR);
+            } else {
+                plain.push_str(word);
+            }
+
+            index += len;
+            continue;
+        }
+
+        plain.push(ch);
+        index += ch.len_utf8();
+    }
+
+    if !plain.is_empty() {
+        spans.push(Span::raw(plain));
+    }
+
+    return spans;
+}
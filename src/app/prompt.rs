@@ -0,0 +1,142 @@
+use std::path::{Path, PathBuf};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use tui::{
+    backend::Backend,
+    layout::{Alignment, Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use super::line_edit::LineEdit;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PromptKind {
+    MarkGlob,
+    Filter,
+    Command,
+    CreateDir,
+    CreateFile,
+}
+
+pub struct Prompt {
+    kind: PromptKind,
+    title: String,
+    input: LineEdit,
+    // Paths typed into the prompt are completed with Tab relative to it
+    completion_dir: Option<PathBuf>,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, title: impl ToString) -> Self {
+        return Prompt {
+            kind,
+            title: title.to_string(),
+            input: LineEdit::new(""),
+            completion_dir: None,
+        };
+    }
+
+    pub fn with_input(mut self, input: impl ToString) -> Self {
+        self.input.set_text(input);
+        return self;
+    }
+
+    // Browsed with Up and Down
+    pub fn with_history(mut self, history: &[String]) -> Self {
+        self.input = LineEdit::new(self.input.get_text()).with_history(history);
+        return self;
+    }
+
+    pub fn with_completion(mut self, dir: &Path) -> Self {
+        self.completion_dir = Some(dir.to_path_buf());
+        return self;
+    }
+
+    pub fn get_kind(&self) -> PromptKind {
+        return self.kind;
+    }
+
+    pub fn get_input(&self) -> &str {
+        return self.input.get_text();
+    }
+
+    pub fn handle_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Up => self.input.previous_history_entry(),
+            KeyCode::Down => self.input.next_history_entry(),
+            KeyCode::Tab => {
+                if let Some(dir) = &self.completion_dir {
+                    self.input
+                        .complete_path(dir, self.kind == PromptKind::Command);
+                }
+            }
+            _ => self.input.handle_key(key),
+        }
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        self.input.paste(text);
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
+        // The command line sits at the bottom, the others in the middle
+        let (area, text): (Rect, Text) = match self.kind {
+            PromptKind::Command => (
+                Layout::default()
+                    .direction(Direction::Vertical)
+                    .constraints([Constraint::Min(0), Constraint::Length(3)].as_ref())
+                    .split(f.size())[1],
+                Text::from(Spans::from(self.input.get_spans(Style::default()))),
+            ),
+            _ => (
+                get_centered_area(f.size()),
+                Text::from(vec![
+                    Spans::from(self.input.get_spans(Style::default())),
+                    Spans::from(""),
+                    Spans::from("[Enter to confirm, Esc to cancel, Up/Down for the history]"),
+                ]),
+            ),
+        };
+
+        let prompt_msg: Paragraph = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .title(&self.title[..])
+                    .borders(Borders::ALL),
+            )
+            .style(Style::default().fg(Color::White).bg(Color::Black))
+            .alignment(Alignment::Left);
+
+        f.render_widget(Clear, area);
+        f.render_widget(prompt_msg, area);
+    }
+}
+
+fn get_centered_area(size: Rect) -> Rect {
+    let vertical_layout: Vec<Rect> = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage(40),
+                Constraint::Length(5),
+                Constraint::Percentage(40),
+            ]
+            .as_ref(),
+        )
+        .split(size);
+
+    return Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage(20),
+                Constraint::Percentage(60),
+                Constraint::Percentage(20),
+            ]
+            .as_ref(),
+        )
+        .split(vertical_layout[1])[1];
+}
@@ -0,0 +1,522 @@
+use std::{
+    collections::HashSet,
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use chrono::{DateTime, Local};
+use regex::{Captures, Regex};
+
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum CaseConversion {
+    Keep,
+    Lower,
+    Upper,
+    // First letter of every word upper case
+    Title,
+}
+
+impl CaseConversion {
+    pub const ALL: [CaseConversion; 4] = [
+        CaseConversion::Keep,
+        CaseConversion::Lower,
+        CaseConversion::Upper,
+        CaseConversion::Title,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        return match self {
+            CaseConversion::Keep => "keep",
+            CaseConversion::Lower => "lower",
+            CaseConversion::Upper => "upper",
+            CaseConversion::Title => "title",
+        };
+    }
+
+    fn apply(&self, text: &str) -> String {
+        return match self {
+            CaseConversion::Keep => text.to_string(),
+            CaseConversion::Lower => text.to_lowercase(),
+            CaseConversion::Upper => text.to_uppercase(),
+            CaseConversion::Title => {
+                let mut result: String = String::new();
+                let mut is_word_start: bool = true;
+
+                for ch in text.chars() {
+                    match is_word_start {
+                        true => result.extend(ch.to_uppercase()),
+                        false => result.extend(ch.to_lowercase()),
+                    }
+                    is_word_start = !ch.is_alphanumeric();
+                }
+
+                result
+            }
+        };
+    }
+}
+
+// Computes the new names of a bulk rename
+// The template may contain the capture groups of the regex ($1, ${name}), the
+// counter ({n}, {n:3} zero padded to 3 digits) and the modification date
+// ({date}, {date:%Y%m%d} with a chrono format)
+pub struct RenameRule {
+    regex: Regex,
+    template: String,
+    case: CaseConversion,
+    counter_start: i64,
+    counter_step: i64,
+}
+
+impl RenameRule {
+    pub fn new(
+        find: &str,
+        template: &str,
+        case: CaseConversion,
+        counter_start: &str,
+        counter_step: &str,
+    ) -> Result<Self, String> {
+        // Without a regex the template replaces the whole name
+        let regex: Regex = match find.is_empty() {
+            true => Regex::new("(?s)^.*$").unwrap(),
+            false => {
+                Regex::new(find).map_err(|x| format!["Invalid regex {} [Error: {}]", find, x])?
+            }
+        };
+
+        return Ok(RenameRule {
+            regex,
+            template: template.to_string(),
+            case,
+            counter_start: parse_number(counter_start, "counter start")?,
+            counter_step: parse_number(counter_step, "counter step")?,
+        });
+    }
+
+    // Every match of the regex is replaced, the counter counts the paths
+    fn apply(&self, path: &Path, index: usize) -> Result<String, String> {
+        let name: String = get_file_name(path);
+        let counter: i64 = (index as i64)
+            .checked_mul(self.counter_step)
+            .and_then(|x| x.checked_add(self.counter_start))
+            .ok_or_else(|| format!["The counter of {} is out of range", name])?;
+        let modified: Option<SystemTime> =
+            fs::symlink_metadata(path).and_then(|x| x.modified()).ok();
+
+        let new_name: String = self
+            .regex
+            .replace_all(&name, |caps: &Captures| {
+                expand(&self.template, caps, counter, modified)
+            })
+            .to_string();
+
+        return Ok(self.case.apply(&new_name));
+    }
+
+    // Pairs of old and new paths, unchanged names are left out
+    pub fn plan(&self, paths: &[PathBuf]) -> Result<Vec<(PathBuf, PathBuf)>, String> {
+        let mut plan: Vec<(PathBuf, PathBuf)> = Vec::new();
+
+        for (index, path) in paths.iter().enumerate() {
+            let new_path: PathBuf = path.with_file_name(self.apply(path, index)?);
+
+            if new_path != *path {
+                plan.push((path.clone(), new_path));
+            }
+        }
+
+        return Ok(plan);
+    }
+}
+
+fn parse_number(text: &str, label: &str) -> Result<i64, String> {
+    return text
+        .trim()
+        .parse::<i64>()
+        .map_err(|_| format!["Invalid {} {}", label, text]);
+}
+
+// Replaces the tokens of the template, unknown tokens are kept as they are
+fn expand(template: &str, caps: &Captures, counter: i64, modified: Option<SystemTime>) -> String {
+    let mut result: String = String::new();
+    let mut rest: &str = template;
+
+    while let Some(start) = rest.find(['$', '{']) {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix('$') {
+            // ${name} or $1
+            let (token, len): (&str, usize) = match after.strip_prefix('{') {
+                Some(x) => match x.find('}') {
+                    Some(end) => (&x[..end], end + 3),
+                    None => ("", 1),
+                },
+                None => {
+                    let digits: usize = after
+                        .find(|x: char| !x.is_ascii_digit())
+                        .unwrap_or(after.len());
+                    (&after[..digits], digits + 1)
+                }
+            };
+
+            let group = match token.parse::<usize>() {
+                _ if token.is_empty() => None,
+                Ok(x) => Some(caps.get(x)),
+                Err(_) => Some(caps.name(token)),
+            };
+
+            match group {
+                Some(x) => {
+                    result.push_str(x.map(|x| x.as_str()).unwrap_or(""));
+                    rest = &rest[len..];
+                }
+                None => {
+                    result.push('$');
+                    rest = &rest[1..];
+                }
+            }
+            continue;
+        }
+
+        let end: usize = match rest.find('}') {
+            Some(x) => x,
+            None => break,
+        };
+
+        let token: &str = &rest[1..end];
+        let (key, argument) = match token.split_once(':') {
+            Some((key, argument)) => (key, Some(argument)),
+            None => (token, None),
+        };
+
+        match (key, argument) {
+            ("n", None) => result.push_str(&counter.to_string()),
+            ("n", Some(width)) => match width.parse::<usize>() {
+                Ok(width) => result.push_str(&format!["{:0width$}", counter, width = width]),
+                Err(_) => result.push_str(&rest[..=end]),
+            },
+            ("date", _) => {
+                let time: Option<DateTime<Local>> = modified.map(|x| x.into());
+                let format: &str = argument.unwrap_or(DEFAULT_DATE_FORMAT);
+                let mut date: String = String::new();
+
+                // Invalid formats are reported as fmt errors
+                match time.map(|x| write!(date, "{}", x.format(format))) {
+                    Some(Ok(_)) => result.push_str(&date),
+                    _ => result.push_str(&rest[..=end]),
+                }
+            }
+            _ => result.push_str(&rest[..=end]),
+        }
+
+        rest = &rest[end + 1..];
+    }
+
+    result.push_str(rest);
+    return result;
+}
+
+// Names must not be empty or contain a path separator
+pub fn check_name(name: &str) -> Result<(), String> {
+    if name.is_empty() || name == "." || name == ".." {
+        return Err(format!["Invalid name {}", name]);
+    }
+
+    if name.contains('/') || name.contains(std::path::MAIN_SEPARATOR) {
+        return Err(format![
+            "The name {} must not contain a path separator",
+            name
+        ]);
+    }
+
+    return Ok(());
+}
+
+// Finds new names which are invalid, used twice or taken by other entries
+pub fn check_plan(plan: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+    let old_paths: HashSet<&PathBuf> = plan.iter().map(|(old, _)| old).collect();
+    let mut new_paths: HashSet<&PathBuf> = HashSet::new();
+    let mut errors: Vec<String> = Vec::new();
+
+    for (old, new) in plan {
+        // Separators in the new name move the entry to another folder
+        if new.parent() != old.parent() {
+            errors.push(format![
+                "The new name of {} must not contain a path separator",
+                old.display()
+            ]);
+        } else if let Err(error) = check_name(&get_file_name(new)) {
+            errors.push(error);
+        } else if !new_paths.insert(new) {
+            errors.push(format![
+                "{} is the new name of several entries",
+                new.display()
+            ]);
+        } else if fs::symlink_metadata(new).is_ok()
+            && !old_paths.contains(new)
+            && !is_same_file(old, new)
+        {
+            errors.push(format!["{} already exists", new.display()]);
+        }
+    }
+
+    if errors.is_empty() {
+        return Ok(());
+    }
+
+    return Err(format!["Nothing was renamed\n\n{}", errors.join("\n")]);
+}
+
+// Renames via temporary names first so that names can be swapped
+// Nothing is renamed if one of the steps fails
+pub fn rename_all(plan: &[(PathBuf, PathBuf)]) -> Result<(), String> {
+    let mut temp_paths: Vec<PathBuf> = Vec::new();
+
+    for (index, (old, _)) in plan.iter().enumerate() {
+        let temp: PathBuf = old.with_file_name(format![
+            ".sfmanager-rename-{}-{}",
+            std::process::id(),
+            index
+        ]);
+
+        if let Err(error) = rename_no_replace(old, &temp) {
+            let mut errors: Vec<String> = vec![format![
+                "Failed to rename {} [Error: {}]",
+                old.display(),
+                error
+            ]];
+            restore_old_names(&temp_paths, plan, &mut errors);
+            return Err(errors.join("\n"));
+        }
+
+        temp_paths.push(temp);
+    }
+
+    for (index, (temp, (old, new))) in temp_paths.iter().zip(plan.iter()).enumerate() {
+        if let Err(error) = rename_no_replace(temp, new) {
+            let mut errors: Vec<String> = vec![format![
+                "Failed to rename {} [Error: {}]",
+                old.display(),
+                error
+            ]];
+
+            // The new names have to be freed first, they may be the old names
+            // of other entries
+            for (temp, (_, new)) in temp_paths[..index].iter().zip(plan.iter()).rev() {
+                if let Err(error) = rename_no_replace(new, temp) {
+                    errors.push(format![
+                        "Failed to undo the rename of {} [Error: {}]",
+                        new.display(),
+                        error
+                    ]);
+                }
+            }
+
+            restore_old_names(&temp_paths, plan, &mut errors);
+            return Err(errors.join("\n"));
+        }
+    }
+
+    return Ok(());
+}
+
+// Puts back what was already moved away to a temporary name in reverse order
+fn restore_old_names(
+    temp_paths: &[PathBuf],
+    plan: &[(PathBuf, PathBuf)],
+    errors: &mut Vec<String>,
+) {
+    for (temp, (old, _)) in temp_paths.iter().zip(plan.iter()).rev() {
+        if let Err(error) = rename_no_replace(temp, old) {
+            errors.push(format![
+                "{} is left as {} [Error: {}]",
+                old.display(),
+                temp.display(),
+                error
+            ]);
+        }
+    }
+}
+
+// Fails instead of replacing an entry which appeared in the meantime
+fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    if fs::symlink_metadata(to).is_ok() && !is_same_file(from, to) {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!["{} already exists", to.display()],
+        ));
+    }
+
+    return fs::rename(from, to);
+}
+
+// Renaming to another case of the same name is fine on case-insensitive file systems
+#[cfg(unix)]
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+
+    return match (fs::symlink_metadata(a), fs::symlink_metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev() && a.ino() == b.ino(),
+        _ => false,
+    };
+}
+
+#[cfg(not(unix))]
+pub fn is_same_file(a: &Path, b: &Path) -> bool {
+    return a.to_string_lossy().to_lowercase() == b.to_string_lossy().to_lowercase();
+}
+
+fn get_file_name(path: &Path) -> String {
+    return path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Empty folder below the temp dir, removed again by the caller
+    fn get_test_dir(name: &str) -> PathBuf {
+        let dir: PathBuf = std::env::temp_dir().join(format![
+            "sfmanager-rename-test-{}-{}",
+            std::process::id(),
+            name
+        ]);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        return dir;
+    }
+
+    fn get_new_names(rule: &RenameRule, paths: &[PathBuf]) -> Vec<String> {
+        return rule
+            .plan(paths)
+            .unwrap()
+            .iter()
+            .map(|(_, new)| get_file_name(new))
+            .collect();
+    }
+
+    fn new_rule(find: &str, template: &str) -> RenameRule {
+        return RenameRule::new(find, template, CaseConversion::Keep, "1", "1").unwrap();
+    }
+
+    #[test]
+    fn expands_capture_groups() {
+        let rule: RenameRule = new_rule(r"(?P<name>\w+)-(\d+)", "${name}_$2_$9");
+
+        assert_eq!(
+            get_new_names(&rule, &[PathBuf::from("/tmp/photo-12.jpg")]),
+            ["photo_12_.jpg"]
+        );
+    }
+
+    #[test]
+    fn expands_padded_counter() {
+        let rule: RenameRule =
+            RenameRule::new("", "img{n:3}", CaseConversion::Keep, "9", "2").unwrap();
+
+        assert_eq!(
+            get_new_names(&rule, &[PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")]),
+            ["img009", "img011"]
+        );
+    }
+
+    #[test]
+    fn expands_modification_date() {
+        let dir: PathBuf = get_test_dir("date");
+        let path: PathBuf = dir.join("notes.txt");
+        fs::write(&path, "").unwrap();
+
+        let rule: RenameRule = new_rule("^.*$", "{date:%Y} $0 {date:%Q");
+        let year: String = Local::now().format("%Y").to_string();
+
+        assert_eq!(
+            get_new_names(&rule, &[path]),
+            [format!["{} notes.txt {{date:%Q", year]]
+        );
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reports_counter_overflow() {
+        let rule: RenameRule =
+            RenameRule::new("", "{n}", CaseConversion::Keep, "1", &i64::MAX.to_string()).unwrap();
+
+        assert!(rule
+            .plan(&[PathBuf::from("/tmp/a"), PathBuf::from("/tmp/b")])
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_duplicate_names() {
+        let dir: PathBuf = get_test_dir("duplicate");
+        let plan: Vec<(PathBuf, PathBuf)> = new_rule("", "same")
+            .plan(&[dir.join("a"), dir.join("b")])
+            .unwrap();
+
+        assert!(check_plan(&plan).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rejects_existing_targets() {
+        let dir: PathBuf = get_test_dir("existing");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("c"), "c").unwrap();
+
+        let plan: Vec<(PathBuf, PathBuf)> = vec![(dir.join("a"), dir.join("c"))];
+
+        assert!(check_plan(&plan).is_err());
+        assert_eq!(fs::read_to_string(dir.join("c")).unwrap(), "c");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn swaps_names() {
+        let dir: PathBuf = get_test_dir("swap");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+
+        let plan: Vec<(PathBuf, PathBuf)> = vec![
+            (dir.join("a"), dir.join("b")),
+            (dir.join("b"), dir.join("a")),
+        ];
+
+        assert!(check_plan(&plan).is_ok());
+        assert!(rename_all(&plan).is_ok());
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "a");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 2);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn failed_rename_keeps_all_entries() {
+        let dir: PathBuf = get_test_dir("failed");
+        fs::write(dir.join("a"), "a").unwrap();
+        fs::write(dir.join("b"), "b").unwrap();
+        fs::write(dir.join("c"), "c").unwrap();
+
+        // a takes the name of b, which is moved on to c, but c was created after
+        // the plan was checked
+        let plan: Vec<(PathBuf, PathBuf)> = vec![
+            (dir.join("a"), dir.join("b")),
+            (dir.join("b"), dir.join("c")),
+        ];
+
+        assert!(rename_all(&plan).is_err());
+        assert_eq!(fs::read_to_string(dir.join("a")).unwrap(), "a");
+        assert_eq!(fs::read_to_string(dir.join("b")).unwrap(), "b");
+        assert_eq!(fs::read_to_string(dir.join("c")).unwrap(), "c");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
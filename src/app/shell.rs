@@ -0,0 +1,182 @@
+use std::{
+    env,
+    ffi::{OsStr, OsString},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+#[cfg(unix)]
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+#[cfg(windows)]
+use std::os::windows::ffi::{OsStrExt, OsStringExt};
+
+// Paths which the placeholders of a command line stand for
+pub struct Placeholders {
+    // Entry under the cursor
+    pub cur_obj: PathBuf,
+    // Marked entries, or the one under the cursor
+    pub selected: Vec<PathBuf>,
+    pub dir: PathBuf,
+    pub other_dir: PathBuf,
+}
+
+impl Placeholders {
+    // %f entry under the cursor, %s selected entries, %d folder of the panel,
+    // %D folder of the other panel and %% a literal %
+    // The paths are quoted for the shell and kept byte for byte
+    pub fn expand(&self, text: &str) -> OsString {
+        let mut result: OsString = OsString::new();
+        let mut chars = text.chars();
+
+        while let Some(ch) = chars.next() {
+            if ch != '%' {
+                result.push(ch.to_string());
+                continue;
+            }
+
+            match chars.next() {
+                Some('f') => result.push(quote(&self.cur_obj)),
+                Some('s') => {
+                    for (index, path) in self.selected.iter().enumerate() {
+                        if index > 0 {
+                            result.push(" ");
+                        }
+                        result.push(quote(path));
+                    }
+                }
+                Some('d') => result.push(quote(&self.dir)),
+                Some('D') => result.push(quote(&self.other_dir)),
+                Some('%') => result.push("%"),
+                Some(x) => {
+                    result.push("%");
+                    result.push(x.to_string());
+                }
+                None => result.push("%"),
+            }
+        }
+
+        return result;
+    }
+}
+
+// Runs the text in $SHELL, the command line interpreter on Windows
+pub fn get_shell_command(text: &OsStr, dir: &Path) -> Command {
+    let mut command: Command;
+
+    if cfg!(windows) {
+        command = Command::new(env::var("COMSPEC").unwrap_or_else(|_| String::from("cmd")));
+        command.arg("/C");
+    } else {
+        command = Command::new(env::var("SHELL").unwrap_or_else(|_| String::from("sh")));
+        command.arg("-c");
+    }
+
+    command.arg(text).current_dir(dir);
+    return command;
+}
+
+#[cfg(unix)]
+fn quote(path: &Path) -> OsString {
+    let mut quoted: Vec<u8> = vec![b'\''];
+
+    for byte in path.as_os_str().as_bytes() {
+        match byte {
+            b'\'' => quoted.extend_from_slice(b"'\\''"),
+            x => quoted.push(*x),
+        }
+    }
+
+    quoted.push(b'\'');
+    return OsString::from_vec(quoted);
+}
+
+// cmd expands %name% even inside quotes so every % is escaped outside of them
+#[cfg(windows)]
+fn quote(path: &Path) -> OsString {
+    let mut quoted: Vec<u16> = "\"".encode_utf16().collect();
+
+    for unit in path.as_os_str().encode_wide() {
+        match unit == '%' as u16 {
+            true => quoted.extend("\"^%\"".encode_utf16()),
+            false => quoted.push(unit),
+        }
+    }
+
+    quoted.extend("\"".encode_utf16());
+    return OsString::from_wide(&quoted);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_placeholders() -> Placeholders {
+        return Placeholders {
+            cur_obj: PathBuf::from("/home/user/a b.txt"),
+            selected: vec![PathBuf::from("/home/user/one"), PathBuf::from("/tmp/two")],
+            dir: PathBuf::from("/home/user"),
+            other_dir: PathBuf::from("/tmp"),
+        };
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn expands_cur_obj() {
+        assert_eq!(
+            get_placeholders().expand("cat %f"),
+            OsString::from("cat '/home/user/a b.txt'")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn expands_selected_objs() {
+        assert_eq!(
+            get_placeholders().expand("ls %s"),
+            OsString::from("ls '/home/user/one' '/tmp/two'")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn expands_dirs() {
+        assert_eq!(
+            get_placeholders().expand("cp -r %d %D"),
+            OsString::from("cp -r '/home/user' '/tmp'")
+        );
+    }
+
+    #[test]
+    fn keeps_literal_percent() {
+        assert_eq!(
+            get_placeholders().expand("date +%%Y %x %"),
+            OsString::from("date +%Y %x %")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn escapes_single_quotes() {
+        assert_eq!(quote(Path::new("it's")), OsString::from("'it'\\''s'"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_non_utf8_names() {
+        let name: OsString = OsString::from_vec(vec![b'a', 0xff, b'b']);
+
+        assert_eq!(
+            quote(Path::new(&name)).as_bytes(),
+            &[b'\'', b'a', 0xff, b'b', b'\'']
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn escapes_percent_for_cmd() {
+        assert_eq!(
+            quote(Path::new("C:\\100%")),
+            OsString::from("\"C:\\100\"^%\"\"")
+        );
+    }
+}
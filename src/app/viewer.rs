@@ -0,0 +1,583 @@
+use tui::{
+    backend::Backend,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Span, Spans, Text},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use std::{
+    fs,
+    io::{Read, Seek, SeekFrom},
+    path::{Path, PathBuf},
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+
+use super::line_edit::LineEdit;
+
+// The file is read in chunks of this size, it is never loaded as a whole
+const CHUNK_SIZE: usize = 64 * 1024;
+// Longer lines are cut off
+const MAX_LINE_LEN: usize = 16 * 1024;
+const HEX_BYTES_PER_LINE: usize = 16;
+const TAB_WIDTH: usize = 4;
+
+const GUTTER_COLOR: Color = Color::DarkGray;
+const MATCH_COLOR: Color = Color::LightRed;
+
+#[derive(Clone, Copy, PartialEq)]
+enum InputKind {
+    Search,
+    Goto,
+}
+
+// Full screen viewer which streams the file from the disk
+// Text lines are found by indexing the file up to the lines needed so far
+pub struct Viewer {
+    path: PathBuf,
+    file: fs::File,
+    size: u64,
+    // Start offsets of the lines indexed so far
+    line_starts: Vec<u64>,
+    // Offset up to which the file is indexed
+    indexed_len: u64,
+    // First visible line, or row in hex mode
+    top: usize,
+    // Chars skipped on the left if lines are not wrapped
+    column: usize,
+    // Visible rows, updated on every render
+    height: usize,
+    wrap: bool,
+    hex: bool,
+    search: String,
+    input: Option<(InputKind, LineEdit)>,
+    message: String,
+}
+
+impl Viewer {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let metadata: fs::Metadata = fs::metadata(path)
+            .map_err(|x| format!["Failed to open {} [Error: {}]", path.display(), x])?;
+
+        // Opening fifos or devices may block forever
+        if metadata.is_dir() {
+            return Err(format!["{} is a folder", path.display()]);
+        } else if !metadata.is_file() {
+            return Err(format!["{} is not a regular file", path.display()]);
+        }
+
+        let file: fs::File = fs::File::open(path)
+            .map_err(|x| format!["Failed to open {} [Error: {}]", path.display(), x])?;
+        let size: u64 = file.metadata().map(|x| x.len()).unwrap_or(0);
+
+        return Ok(Viewer {
+            path: path.to_path_buf(),
+            file,
+            size,
+            line_starts: vec![0],
+            indexed_len: 0,
+            top: 0,
+            column: 0,
+            height: 1,
+            wrap: false,
+            hex: false,
+            search: String::new(),
+            input: None,
+            message: String::new(),
+        });
+    }
+
+    // Returns false once the viewer is closed
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        self.message.clear();
+
+        if let Some((kind, input)) = self.input.as_mut() {
+            match key.code {
+                KeyCode::Enter => {
+                    let kind: InputKind = *kind;
+                    let input: String = input.get_text().to_string();
+                    self.input = None;
+                    self.confirm_input(kind, input);
+                }
+                KeyCode::Esc => self.input = None,
+                _ => input.handle_key(key),
+            }
+            return true;
+        }
+
+        let page: usize = self.height.max(2) - 1;
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(10) => return false,
+            KeyCode::Up | KeyCode::Char('k') => self.scroll_up(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll_down(1),
+            KeyCode::PageUp => self.scroll_up(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => self.scroll_down(page),
+            KeyCode::Home => self.top = 0,
+            KeyCode::End => self.scroll_to_end(),
+            KeyCode::Left => self.column = self.column.saturating_sub(TAB_WIDTH * 2),
+            KeyCode::Right if !self.wrap => self.column += TAB_WIDTH * 2,
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                self.column = 0;
+            }
+            KeyCode::Char('h') | KeyCode::F(4) => self.toggle_hex(),
+            KeyCode::Char('/') | KeyCode::F(7) => {
+                self.input = Some((InputKind::Search, LineEdit::new(&self.search)))
+            }
+            KeyCode::Char('g') | KeyCode::Char(':') => {
+                self.input = Some((InputKind::Goto, LineEdit::new("")))
+            }
+            KeyCode::Char('n') => self.find_next(),
+            KeyCode::Char('N') => self.find_previous(),
+            _ => {}
+        }
+
+        return true;
+    }
+
+    pub fn paste(&mut self, text: &str) {
+        if let Some((_, input)) = self.input.as_mut() {
+            input.paste(text);
+        }
+    }
+
+    fn confirm_input(&mut self, kind: InputKind, input: String) {
+        match kind {
+            InputKind::Search => {
+                self.search = input;
+
+                // The visible top line is searched as well
+                match self.search_forward(self.get_top_offset()) {
+                    Some(x) => self.jump_to_offset(x),
+                    None => self.message = format!["{} not found", self.search],
+                }
+            }
+            InputKind::Goto => self.goto(&input),
+        }
+    }
+
+    fn line_count(&self) -> usize {
+        return match self.hex {
+            true => (self.size as usize).div_ceil(HEX_BYTES_PER_LINE).max(1),
+            false => self.line_starts.len(),
+        };
+    }
+
+    fn is_indexed(&self) -> bool {
+        return self.indexed_len >= self.size;
+    }
+
+    // Reads the file further until the line is known or the end is reached
+    fn index_to_line(&mut self, line: usize) {
+        while self.line_starts.len() <= line && !self.is_indexed() {
+            self.index_chunk();
+        }
+    }
+
+    fn index_to_offset(&mut self, offset: u64) {
+        while self.indexed_len <= offset && !self.is_indexed() {
+            self.index_chunk();
+        }
+    }
+
+    fn index_chunk(&mut self) {
+        let chunk: Vec<u8> = self.read_at(self.indexed_len, CHUNK_SIZE);
+
+        if chunk.is_empty() {
+            // The file got shorter since it was opened
+            self.size = self.indexed_len;
+            return;
+        }
+
+        for (index, byte) in chunk.iter().enumerate() {
+            let next_start: u64 = self.indexed_len + index as u64 + 1;
+
+            if *byte == b'\n' && next_start < self.size {
+                self.line_starts.push(next_start);
+            }
+        }
+
+        self.indexed_len += chunk.len() as u64;
+    }
+
+    fn read_at(&self, offset: u64, len: usize) -> Vec<u8> {
+        let mut data: Vec<u8> = Vec::new();
+        let mut file: &fs::File = &self.file;
+
+        if file.seek(SeekFrom::Start(offset)).is_ok() {
+            let _ = file.take(len as u64).read_to_end(&mut data);
+        }
+
+        return data;
+    }
+
+    fn read_line(&mut self, line: usize) -> Option<String> {
+        self.index_to_line(line + 1);
+
+        let start: u64 = *self.line_starts.get(line)?;
+        let end: u64 = match self.line_starts.get(line + 1) {
+            Some(x) => *x,
+            None => self.size,
+        };
+
+        let len: usize = ((end - start) as usize).min(MAX_LINE_LEN);
+        let data: Vec<u8> = self.read_at(start, len);
+        let text: String = String::from_utf8_lossy(&data)
+            .trim_end_matches(['\n', '\r'])
+            .replace('\t', &" ".repeat(TAB_WIDTH));
+
+        // Control chars would mess up the terminal
+        return Some(
+            text.chars()
+                .map(|x| match x.is_control() {
+                    true => '.',
+                    false => x,
+                })
+                .collect(),
+        );
+    }
+
+    fn get_top_offset(&self) -> u64 {
+        return match self.hex {
+            true => (self.top * HEX_BYTES_PER_LINE) as u64,
+            false => self.line_starts[self.top],
+        };
+    }
+
+    // Line or hex row containing the offset
+    fn get_line_at(&mut self, offset: u64) -> usize {
+        if self.hex {
+            return offset as usize / HEX_BYTES_PER_LINE;
+        }
+
+        self.index_to_offset(offset);
+        return self.line_starts.partition_point(|x| *x <= offset) - 1;
+    }
+
+    fn jump_to_offset(&mut self, offset: u64) {
+        self.top = self.get_line_at(offset);
+    }
+
+    fn scroll_up(&mut self, count: usize) {
+        self.top = self.top.saturating_sub(count);
+    }
+
+    fn scroll_down(&mut self, count: usize) {
+        if !self.hex {
+            self.index_to_line(self.top + count);
+        }
+
+        self.top = (self.top + count).min(self.line_count() - 1);
+    }
+
+    // Indexes the whole file which takes a while for huge ones
+    fn scroll_to_end(&mut self) {
+        if !self.hex {
+            self.index_to_offset(self.size);
+        }
+
+        self.top = self.line_count().saturating_sub(self.height.max(1));
+    }
+
+    // Keeps the position in the file
+    fn toggle_hex(&mut self) {
+        let offset: u64 = self.get_top_offset();
+        self.hex = !self.hex;
+        self.column = 0;
+        self.jump_to_offset(offset);
+    }
+
+    // Takes a line number, or an offset in hex mode, "0x" is optional there
+    fn goto(&mut self, input: &str) {
+        let input: &str = input.trim();
+
+        if self.hex {
+            let digits: &str = input.trim_start_matches("0x");
+
+            match u64::from_str_radix(digits, 16) {
+                Ok(x) => self.top = self.get_line_at(x.min(self.size)),
+                Err(_) => self.message = format!["Invalid offset {}", input],
+            }
+            return;
+        }
+
+        match input.parse::<usize>() {
+            Ok(x) => {
+                let line: usize = x.max(1) - 1;
+                self.index_to_line(line);
+                self.top = line.min(self.line_count() - 1);
+            }
+            Err(_) => self.message = format!["Invalid line number {}", input],
+        }
+    }
+
+    fn find_next(&mut self) {
+        if self.search.is_empty() {
+            return;
+        }
+
+        // Matches in the top line are skipped
+        let from: u64 = match self.hex {
+            true => self.get_top_offset() + HEX_BYTES_PER_LINE as u64,
+            false => {
+                self.index_to_line(self.top + 1);
+
+                match self.line_starts.get(self.top + 1) {
+                    Some(x) => *x,
+                    None => self.size,
+                }
+            }
+        };
+
+        match self.search_forward(from) {
+            Some(x) => self.jump_to_offset(x),
+            None => self.message = format!["No more matches of {}", self.search],
+        }
+    }
+
+    fn find_previous(&mut self) {
+        if self.search.is_empty() {
+            return;
+        }
+
+        match self.search_backward(self.get_top_offset()) {
+            Some(x) => self.jump_to_offset(x),
+            None => self.message = format!["No previous matches of {}", self.search],
+        }
+    }
+
+    // Offset of the first match at or after the offset, ASCII case is ignored
+    fn search_forward(&self, from: u64) -> Option<u64> {
+        let needle: Vec<u8> = self.search.to_ascii_lowercase().into_bytes();
+        let mut offset: u64 = from;
+
+        while offset < self.size {
+            // Overlap the chunks so that matches across their borders are found
+            let chunk: Vec<u8> = self.read_at(offset, CHUNK_SIZE + needle.len());
+
+            if let Some(x) = find_bytes(&chunk, &needle) {
+                return Some(offset + x as u64);
+            }
+
+            offset += CHUNK_SIZE as u64;
+        }
+
+        return None;
+    }
+
+    // Offset of the last match starting before the offset
+    fn search_backward(&self, before: u64) -> Option<u64> {
+        let needle: Vec<u8> = self.search.to_ascii_lowercase().into_bytes();
+        let mut found: Option<u64> = None;
+        let mut offset: u64 = 0;
+
+        while offset < before {
+            let len: usize = ((before - offset) as usize).min(CHUNK_SIZE) + needle.len() - 1;
+            let chunk: Vec<u8> = self.read_at(offset, len);
+            let mut start: usize = 0;
+
+            while let Some(x) = find_bytes(&chunk[start..], &needle) {
+                let match_offset: u64 = offset + (start + x) as u64;
+
+                if match_offset >= before {
+                    break;
+                }
+
+                found = Some(match_offset);
+                start += x + 1;
+            }
+
+            offset += CHUNK_SIZE as u64;
+        }
+
+        return found;
+    }
+
+    fn get_text_lines(&mut self, width: usize) -> Vec<Spans<'static>> {
+        let mut lines: Vec<Spans<'static>> = Vec::new();
+        let number_width: usize = (self.top + self.height).to_string().len();
+
+        for line in self.top..self.top + self.height {
+            let text: String = match self.read_line(line) {
+                Some(x) => x,
+                None => break,
+            };
+
+            let text: String = match self.wrap {
+                true => text,
+                false => text.chars().skip(self.column).take(width).collect(),
+            };
+
+            let mut spans: Vec<Span<'static>> = vec![Span::styled(
+                format!["{:>width$} ", line + 1, width = number_width],
+                Style::default().fg(GUTTER_COLOR),
+            )];
+            spans.extend(highlight_matches(&text, &self.search));
+            lines.push(Spans::from(spans));
+        }
+
+        return lines;
+    }
+
+    // Offset, bytes in hex and the printable ASCII chars
+    fn get_hex_lines(&self) -> Vec<Spans<'static>> {
+        let offset: u64 = self.get_top_offset();
+        let data: Vec<u8> = self.read_at(offset, self.height * HEX_BYTES_PER_LINE);
+
+        return data
+            .chunks(HEX_BYTES_PER_LINE)
+            .enumerate()
+            .map(|(index, chunk)| {
+                let hex: String = chunk
+                    .iter()
+                    .map(|x| format!["{:02x}", x])
+                    .collect::<Vec<String>>()
+                    .join(" ");
+
+                let ascii: String = chunk
+                    .iter()
+                    .map(|x| match x.is_ascii_graphic() || *x == b' ' {
+                        true => *x as char,
+                        false => '.',
+                    })
+                    .collect();
+
+                Spans::from(vec![
+                    Span::styled(
+                        format!["{:08x}  ", offset as usize + index * HEX_BYTES_PER_LINE],
+                        Style::default().fg(GUTTER_COLOR),
+                    ),
+                    Span::raw(format![
+                        "{:width$}  {}",
+                        hex,
+                        ascii,
+                        width = HEX_BYTES_PER_LINE * 3 - 1
+                    ]),
+                ])
+            })
+            .collect();
+    }
+
+    fn get_input_spans(&self) -> Option<Spans<'static>> {
+        let (kind, input) = self.input.as_ref()?;
+        let label: &str = match (kind, self.hex) {
+            (InputKind::Search, _) => "Search",
+            (InputKind::Goto, true) => "Go to offset (hex)",
+            (InputKind::Goto, false) => "Go to line",
+        };
+
+        let mut spans: Vec<Span<'static>> = vec![Span::raw(format!["{}: ", label])];
+        spans.extend(input.get_spans(Style::default()));
+        return Some(Spans::from(spans));
+    }
+
+    fn get_status(&self) -> String {
+        if !self.message.is_empty() {
+            return self.message.clone();
+        }
+
+        let position: String = match (self.hex, self.is_indexed()) {
+            (true, _) => format!["Offset {:08x}", self.get_top_offset()],
+            (false, true) => format!["Line {}/{}", self.top + 1, self.line_count()],
+            (false, false) => format!["Line {}", self.top + 1],
+        };
+
+        let percent: u64 = match self.size {
+            0 => 100,
+            _ => self.get_top_offset() * 100 / self.size,
+        };
+
+        return format![
+            "{} ({}%) [Up/Down/PgUp/PgDn scroll, w wrap, h hex, / search, n/N next/previous, g go to, q close]",
+            position, percent
+        ];
+    }
+
+    pub fn render<B: Backend>(&mut self, f: &mut Frame<B>) {
+        let mode: &str = match (self.hex, self.wrap) {
+            (true, _) => "hex",
+            (false, true) => "text, wrapped",
+            (false, false) => "text",
+        };
+
+        let block: Block = Block::default()
+            .title(format!["View: {} [{}]", self.path.display(), mode])
+            .borders(Borders::ALL)
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        let inner: Rect = block.inner(f.size());
+
+        f.render_widget(Clear, f.size());
+        f.render_widget(block, f.size());
+
+        let chunks: Vec<Rect> = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(1)].as_ref())
+            .split(inner);
+
+        self.height = chunks[0].height as usize;
+
+        let lines: Vec<Spans<'static>> = match self.hex {
+            true => self.get_hex_lines(),
+            false => self.get_text_lines(chunks[0].width as usize),
+        };
+
+        let mut content: Paragraph = Paragraph::new(Text::from(lines));
+
+        if self.wrap && !self.hex {
+            content = content.wrap(Wrap { trim: false });
+        }
+
+        // The input line shows its own cursor
+        let status: Paragraph = match self.get_input_spans() {
+            Some(x) => Paragraph::new(x),
+            None => Paragraph::new(self.get_status())
+                .style(Style::default().add_modifier(Modifier::REVERSED)),
+        };
+
+        f.render_widget(content, chunks[0]);
+        f.render_widget(status, chunks[1]);
+    }
+}
+
+// Position of the lowercase needle in the data, ASCII case is ignored
+fn find_bytes(data: &[u8], needle: &[u8]) -> Option<usize> {
+    if needle.is_empty() || data.len() < needle.len() {
+        return None;
+    }
+
+    return data
+        .windows(needle.len())
+        .position(|x| x.eq_ignore_ascii_case(needle));
+}
+
+fn highlight_matches(text: &str, search: &str) -> Vec<Span<'static>> {
+    if search.is_empty() {
+        return vec![Span::raw(text.to_string())];
+    }
+
+    // ASCII lowercase keeps the byte positions
+    let lower_text: String = text.to_ascii_lowercase();
+    let lower_search: String = search.to_ascii_lowercase();
+    let mut spans: Vec<Span<'static>> = Vec::new();
+    let mut start: usize = 0;
+
+    while let Some(x) = lower_text[start..].find(&lower_search) {
+        let match_start: usize = start + x;
+        let match_end: usize = match_start + lower_search.len();
+
+        spans.push(Span::raw(text[start..match_start].to_string()));
+        spans.push(Span::styled(
+            text[match_start..match_end].to_string(),
+            Style::default()
+                .fg(MATCH_COLOR)
+                .add_modifier(Modifier::REVERSED),
+        ));
+        start = match_end;
+    }
+
+    spans.push(Span::raw(text[start..].to_string()));
+    return spans;
+}
@@ -1,22 +1,37 @@
+#![allow(clippy::needless_return, clippy::needless_late_init)]
+
 use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
+    event::{
+        self, DisableBracketedPaste, DisableMouseCapture, EnableBracketedPaste, EnableMouseCapture,
+        Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers,
+    },
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use std::{error::Error, io, time::Duration};
+use std::{
+    error::Error,
+    io,
+    process::{Command, ExitStatus},
+    time::Duration,
+};
 use tui::{
     backend::{Backend, CrosstermBackend},
     Terminal,
 };
 
 mod app;
-use app::App;
+use app::{Action, App};
 
 fn main() -> Result<(), Box<dyn Error>> {
     // Setup terminal
     enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    execute!(
+        stdout,
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
@@ -29,7 +44,8 @@ fn main() -> Result<(), Box<dyn Error>> {
     execute!(
         terminal.backend_mut(),
         LeaveAlternateScreen,
-        DisableMouseCapture
+        DisableMouseCapture,
+        DisableBracketedPaste
     )?;
     terminal.show_cursor()?;
 
@@ -45,43 +61,197 @@ fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<(
         app.thread_ctrl();
         terminal.draw(|f| app.render(f))?;
 
-        if !event::poll(Duration::from_millis(1000)).unwrap() {
+        // Redraw more often while operations and finds report their progress
+        let timeout: Duration = match app.has_jobs()
+            || app.is_searching()
+            || app.is_running_command()
+            || app.is_computing_dir_sizes()
+        {
+            true => Duration::from_millis(200),
+            false => Duration::from_millis(1000),
+        };
+
+        if !event::poll(timeout).unwrap() {
             continue;
         }
 
-        if let Event::Key(key) = event::read()? {
+        let key: KeyEvent = match event::read()? {
+            Event::Key(x) if x.kind == KeyEventKind::Press => x,
+            // Pasted text arrives as a whole so line breaks in it confirm nothing
+            Event::Paste(text) => {
+                app.paste(&text);
+                continue;
+            }
+            _ => continue,
+        };
+
+        // Popups raised in the background cover everything else, so they get the
+        // keys first
+        if app.is_popup() {
             match key.code {
-                KeyCode::F(1) => app.open_help_popup(),
-                KeyCode::F(2) => app.copy_objects(),
-                KeyCode::F(3) => app.move_objects(),
-                KeyCode::F(5) => app.refresh(),
-                KeyCode::F(12) => return Ok(()),
-                KeyCode::Up => app.previous(),
-                KeyCode::Down => app.next(),
-                KeyCode::Home => app.begin(),
-                KeyCode::End => app.end(),
-                KeyCode::Right => app.open_dir(),
-                KeyCode::Enter => {
-                    if app.is_popup() {
-                        app.close_popup();
-                    } else {
-                        app.open();
-                    }
-                }
-                KeyCode::Left => app.leave_dir(),
-                KeyCode::Backspace => app.pop_char_from_search_str(),
-                KeyCode::Tab => app.switch_active_panel(),
-                KeyCode::Delete => app.delete_objects(),
-                KeyCode::Char(x @ ' '..='~') => app.jump_to_first_matching(x),
-                KeyCode::Esc => {
-                    if app.is_popup() {
-                        app.close_popup();
-                    } else {
-                        app.clear_search_str();
-                    }
-                }
+                KeyCode::Enter => app.press_selected_popup_button(),
+                KeyCode::Esc => app.press_escape_popup_button(),
+                KeyCode::Left | KeyCode::BackTab => app.previous_popup_button(),
+                KeyCode::Right | KeyCode::Tab => app.next_popup_button(),
+                KeyCode::Up => app.scroll_popup_up(1),
+                KeyCode::Down => app.scroll_popup_down(1),
+                KeyCode::PageUp => app.scroll_popup_up(10),
+                KeyCode::PageDown => app.scroll_popup_down(10),
+                KeyCode::Char(' ') => app.toggle_popup_option(),
+                KeyCode::Char(x) => app.press_popup_shortcut(x),
+                _ => {}
+            }
+        } else if app.is_prompt() {
+            match key.code {
+                KeyCode::Enter => app.confirm_prompt(),
+                KeyCode::Esc => app.cancel_prompt(),
+                _ => app.edit_prompt(key),
+            }
+        } else if app.is_form() {
+            match key.code {
+                KeyCode::Enter => app.confirm_form(),
+                KeyCode::Esc => app.cancel_form(),
+                KeyCode::Down => app.next_form_field(),
+                KeyCode::Up | KeyCode::BackTab => app.previous_form_field(),
+                _ => app.edit_form(key),
+            }
+        } else if app.is_renaming() {
+            match key.code {
+                KeyCode::Enter => app.confirm_rename(),
+                KeyCode::Esc => app.cancel_rename(),
+                _ => app.edit_rename(key),
+            }
+        } else if app.is_viewer() {
+            app.handle_viewer_key(key);
+        } else if app.is_jobs_view() {
+            // The panel actions are reused so that remapped keys work here as well
+            match app.get_action(key) {
+                Some(Action::Up) => app.previous_job(),
+                Some(Action::Down) => app.next_job(),
+                Some(Action::Delete) => app.cancel_job(),
+                Some(Action::ToggleMark) => app.toggle_pause_job(),
+                Some(Action::Jobs) | Some(Action::ClearSearch) => app.toggle_jobs_view(),
+                Some(Action::Quit) => app.quit(),
                 _ => {}
             }
+        } else {
+            handle_key(&mut app, key);
+        }
+
+        if app.should_quit() {
+            app.shutdown();
+            return Ok(());
+        }
+
+        if let Some((command, wait)) = app.take_external_command() {
+            let result: io::Result<ExitStatus> = run_external_command(terminal, command, wait)?;
+            app.finish_external_command(result);
+        }
+    }
+}
+
+// Hands the terminal over to the program and takes it back once it exits
+// Waiting for Enter keeps the output of the program visible
+fn run_external_command<B: Backend>(
+    terminal: &mut Terminal<B>,
+    mut command: Command,
+    wait: bool,
+) -> io::Result<io::Result<ExitStatus>> {
+    disable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        LeaveAlternateScreen,
+        DisableMouseCapture,
+        DisableBracketedPaste
+    )?;
+    terminal.show_cursor()?;
+
+    let result: io::Result<ExitStatus> = command.status();
+
+    if wait {
+        println!("\nPress Enter to return");
+        io::stdin().read_line(&mut String::new())?;
+    }
+
+    enable_raw_mode()?;
+    execute!(
+        io::stdout(),
+        EnterAlternateScreen,
+        EnableMouseCapture,
+        EnableBracketedPaste
+    )?;
+    terminal.hide_cursor()?;
+    // Everything is drawn again since the screen is blank now
+    terminal.clear()?;
+
+    return Ok(result);
+}
+
+fn handle_key(app: &mut App, key: KeyEvent) {
+    let action: Action = match app.get_action(key) {
+        Some(x) => x,
+        None => {
+            let modified: bool = key
+                .modifiers
+                .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+
+            if let KeyCode::Char(x @ ' '..='~') = key.code {
+                if !modified {
+                    app.jump_to_first_matching(x);
+                }
+            }
+            return;
         }
+    };
+
+    match action {
+        Action::Help => app.open_help_popup(),
+        Action::Copy => app.copy_objects(),
+        Action::Move => app.move_objects(),
+        Action::Jobs => app.toggle_jobs_view(),
+        Action::Refresh => app.reload(),
+        Action::Quit => app.quit(),
+        Action::Up => app.previous(),
+        Action::Down => app.next(),
+        Action::Begin => app.begin(),
+        Action::End => app.end(),
+        Action::EnterDir => app.open_dir(),
+        Action::Open => app.open(),
+        Action::LeaveDir => app.leave_dir(),
+        Action::DeleteSearchChar => app.pop_char_from_search_str(),
+        Action::SwitchPanel => app.switch_active_panel(),
+        Action::Delete => app.delete_objects(),
+        Action::DeletePermanently => app.delete_objects_permanently(),
+        Action::ToggleMark => app.toggle_mark(),
+        Action::MarkAll => app.mark_all(),
+        Action::UnmarkAll => app.unmark_all(),
+        Action::InvertMarks => app.invert_marks(),
+        Action::MarkGlob => app.open_mark_glob_prompt(),
+        Action::ToggleDereference => app.toggle_dereference(),
+        Action::ToggleTrash => app.toggle_trash(),
+        Action::ToggleDetails => app.toggle_full_listing(),
+        Action::NextSortKey => app.next_sort_key(),
+        Action::ToggleSortOrder => app.toggle_sort_order(),
+        Action::ToggleSortCase => app.toggle_sort_case(),
+        Action::ToggleDirsFirst => app.toggle_dirs_first(),
+        Action::ToggleHidden => app.toggle_hidden(),
+        Action::Filter => app.open_filter_prompt(),
+        Action::ClearFilter => app.clear_filter(),
+        Action::NextMatch => app.next_match(),
+        Action::PreviousMatch => app.previous_match(),
+        Action::NextSearchMode => app.next_search_mode(),
+        Action::Find => app.open_find_form(),
+        Action::Grep => app.open_grep_form(),
+        Action::QuickView => app.toggle_quick_view(),
+        Action::View => app.open_viewer(),
+        Action::Edit => app.edit_file(),
+        Action::Pager => app.page_file(),
+        Action::CommandLine => app.open_command_prompt(),
+        Action::Rename => app.start_rename(),
+        Action::CreateDir => app.open_create_dir_prompt(),
+        Action::CreateFile => app.open_create_file_prompt(),
+        Action::DirSize => app.compute_dir_sizes(),
+        Action::DirSizesAll => app.compute_all_dir_sizes(),
+        Action::ClearSearch => app.clear_search_str(),
     }
 }